@@ -1,5 +1,6 @@
 import matplotlib.pyplot as plt
 import csv
+import os
 import sys
 
 def plot_csv(file_path, color):
@@ -25,22 +26,26 @@ def plot_method(
     plt.title('red = actual, blue = ' + name)
     plot_csv(numeric_file_path, 'r')
     plot_csv(actual_file_path, 'b')
-    plt.axis([0, 15, 1, 2])
+    plt.axis([-15, 15, 1, 2])
 
     plt.subplot(122)
     plt.title('Difference in %')
     plot_csv(difference_file_path, 'r')
-    plt.axis([0, 15, 0, 2])
+    plt.axis([-15, 15, 0, 2])
 
-methods = ["galerkin_taylor", "galerkin_fourier", "neumann", "nystrom"]
+methods = ["galerkin_taylor", "galerkin_fourier", "galerkin_legendre", "galerkin_legendre_sloan",
+           "galerkin_spline", "collocation_legendre", "collocation_fourier", "collocation_hat",
+           "neumann", "nystrom", "nystrom_toeplitz", "two_grid"]
 prefixes = ["rational", "exponent"]
-dir = "results"
+dir = sys.argv[1] if len(sys.argv) > 1 else "results"
 
 for m in methods:
     for p in prefixes:
         numeric_file = dir + "/" + p + "_" + m + ".csv"
         actual_file = dir + "/" + p + "_actual.csv"
         diff_file = dir + "/" + p + "_" + m + "_diff.csv"
+        if not os.path.exists(diff_file):
+            continue
         plt.figure(p + "_" + m)
         plot_method(p + "_" + m, numeric_file, actual_file, diff_file)
         plt.savefig(dir + "/" + p + "_" + m + ".png")
\ No newline at end of file
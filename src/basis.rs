@@ -0,0 +1,475 @@
+use crate::{functions::Function, integrals::gauss_legendre};
+
+/// Even functions `φ_0, ..., φ_(dimension - 1)` on `[-width, width]` that a solution is
+/// expanded in.
+pub trait Basis: Sync {
+    fn dimension(&self) -> usize;
+
+    fn evaluate(&self, n: usize, x: f64, width: f64) -> f64;
+
+    /// `∫φ_j φ_k dx` over `[-width, width]`, if it is known in closed form.
+    fn gram(&self, _j: usize, _k: usize, _width: f64) -> Option<f64> {
+        None
+    }
+
+    /// `constant + Σ c_n φ_n`.
+    fn function(&self, coefficients: Vec<f64>, constant: f64, width: f64) -> Box<dyn Function>;
+}
+
+/// `x^(2n)`.
+pub struct EvenPowerBasis {
+    polynome_degree: usize,
+}
+
+impl EvenPowerBasis {
+    pub fn new(polynome_degree: usize) -> Self {
+        Self { polynome_degree }
+    }
+}
+
+struct EvenPowerPolynomial {
+    coefficients: Vec<f64>,
+}
+
+impl EvenPowerPolynomial {
+    fn new(coefficients: Vec<f64>) -> Self {
+        Self { coefficients }
+    }
+}
+
+impl Function for EvenPowerPolynomial {
+    fn get(&self, x: f64) -> f64 {
+        (0..self.coefficients.len())
+            .map(|n| (x).powf((n * 2) as f64) * self.coefficients[n])
+            .sum()
+    }
+}
+
+impl Basis for EvenPowerBasis {
+    fn dimension(&self) -> usize {
+        self.polynome_degree / 2
+    }
+
+    fn evaluate(&self, n: usize, x: f64, _width: f64) -> f64 {
+        x.powi(n as i32 * 2)
+    }
+
+    fn gram(&self, j: usize, k: usize, width: f64) -> Option<f64> {
+        let power = 2 * (j + k) as i32 + 1;
+        Some(2.0 * width.powi(power) / power as f64)
+    }
+
+    fn function(
+        &self,
+        mut coefficients: Vec<f64>,
+        constant: f64,
+        _width: f64,
+    ) -> Box<dyn Function> {
+        coefficients[0] += constant;
+        Box::new(EvenPowerPolynomial::new(coefficients))
+    }
+}
+
+/// `cos(nπx / width)`.
+pub struct CosineBasis {
+    polynome_degree: usize,
+}
+
+impl CosineBasis {
+    pub fn new(polynome_degree: usize) -> Self {
+        Self { polynome_degree }
+    }
+}
+
+struct FourierWithCos {
+    coefs: Vec<f64>,
+    width: f64,
+}
+
+impl FourierWithCos {
+    fn new(coefs: Vec<f64>, width: f64) -> Self {
+        Self { coefs, width }
+    }
+}
+
+impl Function for FourierWithCos {
+    fn get(&self, x: f64) -> f64 {
+        self.coefs
+            .iter()
+            .enumerate()
+            .map(|(n, c)| c * (x * n as f64 * std::f64::consts::PI / self.width).cos())
+            .sum()
+    }
+}
+
+impl Basis for CosineBasis {
+    fn dimension(&self) -> usize {
+        self.polynome_degree / 2
+    }
+
+    fn evaluate(&self, n: usize, x: f64, width: f64) -> f64 {
+        (x * n as f64 * std::f64::consts::PI / width).cos()
+    }
+
+    fn gram(&self, j: usize, k: usize, width: f64) -> Option<f64> {
+        Some(match (j == k, j == 0) {
+            (true, true) => 2.0 * width,
+            (true, false) => width,
+            _ => 0.0,
+        })
+    }
+
+    fn function(&self, mut coefficients: Vec<f64>, constant: f64, width: f64) -> Box<dyn Function> {
+        coefficients[0] += constant;
+        Box::new(FourierWithCos::new(coefficients, width))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolynomialFamily {
+    Legendre,
+    Chebyshev,
+}
+
+impl PolynomialFamily {
+    /// `(α_k(t), β_k)` of the recurrence `p_(k+1)(t) = α_k(t) p_k(t) + β_k p_(k-1)(t)`,
+    /// valid for `k >= 1`; both families start from `p_0 = 1` and `p_1 = t`.
+    fn recurrence(self, k: usize, t: f64) -> (f64, f64) {
+        let k = k as f64;
+        match self {
+            PolynomialFamily::Legendre => ((2.0 * k + 1.0) * t / (k + 1.0), -k / (k + 1.0)),
+            PolynomialFamily::Chebyshev => (2.0 * t, -1.0),
+        }
+    }
+
+    fn evaluate(self, n: usize, t: f64) -> f64 {
+        let (mut p0, mut p1) = (1.0, t);
+        if n == 0 {
+            return p0;
+        }
+        for k in 1..n {
+            let (alpha, beta) = self.recurrence(k, t);
+            let p2 = alpha * p1 + beta * p0;
+            p0 = p1;
+            p1 = p2;
+        }
+        p1
+    }
+
+    /// `∫p_m p_n dt` over `[-1, 1]` for even `m` and `n`.
+    fn gram(self, m: usize, n: usize) -> f64 {
+        match self {
+            PolynomialFamily::Legendre => {
+                if m == n {
+                    2.0 / (2 * n + 1) as f64
+                } else {
+                    0.0
+                }
+            }
+            PolynomialFamily::Chebyshev => {
+                let (sum, difference) = ((m + n) as f64, m.abs_diff(n) as f64);
+                1.0 / (1.0 - sum * sum) + 1.0 / (1.0 - difference * difference)
+            }
+        }
+    }
+}
+
+/// `p_(2n)(x / width)` for Legendre or Chebyshev polynomials `p`.
+pub struct OrthogonalPolynomialBasis {
+    polynome_degree: usize,
+    family: PolynomialFamily,
+}
+
+impl OrthogonalPolynomialBasis {
+    pub fn new(polynome_degree: usize, family: PolynomialFamily) -> Self {
+        Self {
+            polynome_degree,
+            family,
+        }
+    }
+}
+
+/// `Σ c_n p_(2n)(x / width)`, summed with Clenshaw's algorithm.
+struct EvenOrthogonalSeries {
+    coefficients: Vec<f64>,
+    width: f64,
+    family: PolynomialFamily,
+}
+
+impl EvenOrthogonalSeries {
+    fn new(coefficients: Vec<f64>, width: f64, family: PolynomialFamily) -> Self {
+        Self {
+            coefficients,
+            width,
+            family,
+        }
+    }
+}
+
+impl Function for EvenOrthogonalSeries {
+    fn get(&self, x: f64) -> f64 {
+        let t = x / self.width;
+        let degree = 2 * (self.coefficients.len() - 1);
+        let a = |k: usize| {
+            if k.is_multiple_of(2) {
+                self.coefficients[k / 2]
+            } else {
+                0.0
+            }
+        };
+
+        let (mut b1, mut b2) = (0.0, 0.0);
+        for k in (1..=degree).rev() {
+            let (alpha, _) = self.family.recurrence(k, t);
+            let (_, beta) = self.family.recurrence(k + 1, t);
+            let b0 = a(k) + alpha * b1 + beta * b2;
+            b2 = b1;
+            b1 = b0;
+        }
+        let (_, beta) = self.family.recurrence(1, t);
+
+        a(0) + t * b1 + beta * b2
+    }
+}
+
+impl Basis for OrthogonalPolynomialBasis {
+    fn dimension(&self) -> usize {
+        self.polynome_degree / 2
+    }
+
+    fn evaluate(&self, n: usize, x: f64, width: f64) -> f64 {
+        self.family.evaluate(2 * n, x / width)
+    }
+
+    fn gram(&self, j: usize, k: usize, width: f64) -> Option<f64> {
+        Some(width * self.family.gram(2 * j, 2 * k))
+    }
+
+    fn function(&self, mut coefficients: Vec<f64>, constant: f64, width: f64) -> Box<dyn Function> {
+        coefficients[0] += constant;
+        Box::new(EvenOrthogonalSeries::new(coefficients, width, self.family))
+    }
+}
+
+/// B-splines `B_n(|x| / width)` of the given degree on the mesh `(i / elements)^grading` of
+/// `[0, 1]`, with the end knots repeated. Degree 1 gives hat functions, a grading above 1
+/// refines the mesh towards the origin. The even extension lets the basis represent a cusp
+/// at `x = 0`, and each function vanishes outside a few elements, so the Gram matrix is banded.
+#[derive(Debug, Clone)]
+pub struct BSplineBasis {
+    degree: usize,
+    knots: Vec<f64>,
+    gauss_nodes: Vec<f64>,
+    gauss_weights: Vec<f64>,
+}
+
+impl BSplineBasis {
+    pub fn new(degree: usize, elements: usize, grading: f64) -> Self {
+        let mesh = (0..=elements).map(|i| (i as f64 / elements as f64).powf(grading));
+        let knots = std::iter::repeat_n(0.0, degree)
+            .chain(mesh)
+            .chain(std::iter::repeat_n(1.0, degree))
+            .collect();
+        // Exact for the products of two basis functions on an element.
+        let (gauss_nodes, gauss_weights) = gauss_legendre(degree + 1);
+
+        Self {
+            degree,
+            knots,
+            gauss_nodes,
+            gauss_weights,
+        }
+    }
+
+    /// Piecewise linear hat functions.
+    pub fn hat(elements: usize, grading: f64) -> Self {
+        Self::new(1, elements, grading)
+    }
+
+    pub fn cubic(elements: usize, grading: f64) -> Self {
+        Self::new(3, elements, grading)
+    }
+
+    /// Index `i` of the knot interval `[knots[i], knots[i + 1])` holding `t ∈ [0, 1]`,
+    /// the basis functions `i - degree ..= i` are the ones not vanishing there.
+    fn span(&self, t: f64) -> usize {
+        let last = self.knots.len() - self.degree - 2;
+        let span = self.knots.partition_point(|&knot| knot <= t) - 1;
+        span.clamp(self.degree, last)
+    }
+
+    /// Values of the basis functions `span - degree ..= span` at `t`, by the Cox-de Boor
+    /// recursion.
+    fn nonzero(&self, span: usize, t: f64) -> Vec<f64> {
+        let mut values = vec![1.0];
+        for j in 1..=self.degree {
+            let mut saved = 0.0;
+            for (r, value) in values.iter_mut().enumerate() {
+                let (left, right) = (self.knots[span + r + 1 - j], self.knots[span + r + 1]);
+                let temp = *value / (right - left);
+                *value = saved + (right - t) * temp;
+                saved = (t - left) * temp;
+            }
+            values.push(saved);
+        }
+        values
+    }
+
+    fn value(&self, n: usize, t: f64) -> f64 {
+        if !(0.0..=1.0).contains(&t) {
+            return 0.0;
+        }
+        let span = self.span(t);
+        if n > span || n + self.degree < span {
+            return 0.0;
+        }
+        self.nonzero(span, t)[n + self.degree - span]
+    }
+}
+
+impl Basis for BSplineBasis {
+    fn dimension(&self) -> usize {
+        self.knots.len() - self.degree - 1
+    }
+
+    fn evaluate(&self, n: usize, x: f64, width: f64) -> f64 {
+        self.value(n, x.abs() / width)
+    }
+
+    fn gram(&self, j: usize, k: usize, width: f64) -> Option<f64> {
+        if j.abs_diff(k) > self.degree {
+            return Some(0.0);
+        }
+
+        let integral: f64 = (j.max(k)..=j.min(k) + self.degree)
+            .map(|i| (self.knots[i], self.knots[i + 1]))
+            .filter(|(a, b)| a < b)
+            .map(|(a, b)| {
+                self.gauss_nodes
+                    .iter()
+                    .zip(&self.gauss_weights)
+                    .map(|(node, weight)| {
+                        let t = (a + b) / 2.0 + node * (b - a) / 2.0;
+                        weight * (b - a) / 2.0 * self.value(j, t) * self.value(k, t)
+                    })
+                    .sum::<f64>()
+            })
+            .sum();
+
+        Some(2.0 * width * integral)
+    }
+
+    fn function(&self, coefficients: Vec<f64>, constant: f64, width: f64) -> Box<dyn Function> {
+        Box::new(BSplineSeries {
+            basis: self.clone(),
+            coefficients,
+            constant,
+            width,
+        })
+    }
+}
+
+/// `constant + Σ c_n B_n(|x| / width)`, which is `constant` outside `[-width, width]`.
+struct BSplineSeries {
+    basis: BSplineBasis,
+    coefficients: Vec<f64>,
+    constant: f64,
+    width: f64,
+}
+
+impl Function for BSplineSeries {
+    fn get(&self, x: f64) -> f64 {
+        let t = x.abs() / self.width;
+        if t > 1.0 {
+            return self.constant;
+        }
+
+        let span = self.basis.span(t);
+        let degree = self.basis.degree;
+        self.constant
+            + self
+                .basis
+                .nonzero(span, t)
+                .iter()
+                .zip(&self.coefficients[span - degree..=span])
+                .map(|(value, c)| value * c)
+                .sum::<f64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrals::{GaussLegendreIntegrator, Integrator};
+
+    #[test]
+    fn closed_form_gram_entries_match_quadrature() {
+        let width = 3.0;
+        // The pieces are aligned with the uniform spline mesh `3 i / 8`.
+        let integrator = GaussLegendreIntegrator::composite(20, 16);
+        let bases: [&dyn Basis; 6] = [
+            &EvenPowerBasis::new(12),
+            &CosineBasis::new(12),
+            &OrthogonalPolynomialBasis::new(12, PolynomialFamily::Legendre),
+            &OrthogonalPolynomialBasis::new(12, PolynomialFamily::Chebyshev),
+            &BSplineBasis::hat(8, 1.0),
+            &BSplineBasis::cubic(8, 1.0),
+        ];
+        for basis in bases {
+            for j in 0..basis.dimension() {
+                for k in 0..basis.dimension() {
+                    let closed = basis.gram(j, k, width).unwrap();
+                    let quadrature = integrator.integrate(
+                        &|x: f64| basis.evaluate(j, x, width) * basis.evaluate(k, x, width),
+                        -width,
+                        width,
+                    );
+                    assert!(
+                        (closed - quadrature).abs() < 1e-10 * quadrature.abs().max(1.0),
+                        "({j}, {k}): {closed} != {quadrature}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn functions_sum_the_series() {
+        let width = 2.0;
+        let coefficients = vec![0.3, -0.2, 0.5, 0.1];
+        let bases: [&dyn Basis; 6] = [
+            &EvenPowerBasis::new(8),
+            &CosineBasis::new(8),
+            &OrthogonalPolynomialBasis::new(8, PolynomialFamily::Legendre),
+            &OrthogonalPolynomialBasis::new(8, PolynomialFamily::Chebyshev),
+            &BSplineBasis::hat(3, 2.0),
+            &BSplineBasis::cubic(1, 1.0),
+        ];
+        for basis in bases {
+            let series = basis.function(coefficients.clone(), 1.0, width);
+            for x in [0.0, 0.7, -1.3, 2.0] {
+                let direct: f64 = coefficients
+                    .iter()
+                    .enumerate()
+                    .map(|(n, c)| c * basis.evaluate(n, x, width))
+                    .sum();
+                assert!((series.get(x) - 1.0 - direct).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn b_splines_on_a_graded_mesh_sum_to_one() {
+        let width = 3.0;
+        for basis in [BSplineBasis::hat(10, 2.0), BSplineBasis::cubic(10, 2.5)] {
+            for i in 0..=60 {
+                let x = -width + i as f64 * 0.1;
+                let sum: f64 = (0..basis.dimension())
+                    .map(|n| basis.evaluate(n, x, width))
+                    .sum();
+                assert!((sum - 1.0).abs() < 1e-12, "{x}: {sum}");
+            }
+            assert_eq!(basis.gram(0, basis.degree + 1, width), Some(0.0));
+        }
+    }
+}
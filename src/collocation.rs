@@ -0,0 +1,197 @@
+use crate::{
+    basis::Basis,
+    functions::Function,
+    integrals::Integrator,
+    linear_equation::LinearEquationSolver,
+    method::{check_finite, Method, SolveError},
+};
+
+use rayon::prelude::*;
+
+/// Points on `[0, width]` where the equation is enforced, one per basis function.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CollocationPoints {
+    /// The nonnegative half of the Chebyshev points of `[-width, width]`.
+    Chebyshev,
+    /// Equally spaced points from `0` to `width`.
+    Uniform,
+    /// `width (i / (count - 1))^grading`, crowded towards `0` for a grading above 1, like
+    /// the mesh of `BSplineBasis::new`.
+    Graded(f64),
+    Custom(Vec<f64>),
+}
+
+impl CollocationPoints {
+    fn points(&self, count: usize, width: f64) -> Vec<f64> {
+        match self {
+            CollocationPoints::Chebyshev => (0..count)
+                .map(|i| {
+                    width * ((2 * i + 1) as f64 * std::f64::consts::PI / (4 * count) as f64).cos()
+                })
+                .collect(),
+            CollocationPoints::Uniform => CollocationPoints::Graded(1.0).points(count, width),
+            CollocationPoints::Graded(_) if count == 1 => vec![0.0],
+            CollocationPoints::Graded(grading) => (0..count)
+                .map(|i| width * (i as f64 / (count - 1) as f64).powf(*grading))
+                .collect(),
+            CollocationPoints::Custom(points) => points.clone(),
+        }
+    }
+}
+
+/// Expands the solution in a basis and enforces the equation exactly at the collocation
+/// points, so each matrix entry is a single integral.
+pub struct CollocationMethod<B: Basis> {
+    integrator: Box<dyn Integrator>,
+    equation_solver: Box<dyn LinearEquationSolver>,
+    basis: B,
+    points: CollocationPoints,
+}
+
+impl<B: Basis> CollocationMethod<B> {
+    pub fn new(
+        integrator: Box<dyn Integrator>,
+        equation_solver: Box<dyn LinearEquationSolver>,
+        basis: B,
+        points: CollocationPoints,
+    ) -> Self {
+        Self {
+            integrator,
+            equation_solver,
+            basis,
+            points,
+        }
+    }
+}
+
+impl<B: Basis> Method for CollocationMethod<B> {
+    fn solve<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<Box<dyn Function + 'a>, SolveError> {
+        if !width.is_finite() {
+            return Err(SolveError::UnboundedDomain);
+        }
+        let dimension = self.basis.dimension();
+        let points = self.points.points(dimension, width);
+        if points.len() != dimension {
+            return Err(SolveError::DimensionMismatch {
+                expected: dimension,
+                found: points.len(),
+            });
+        }
+
+        let kernel = |x: f64, y: f64| {
+            (birth_probability.get(y - x) + birth_probability.get(-y - x))
+                / (1.0 + death_probability.get(x))
+        };
+        let f = |x: f64| {
+            (birth_probability.get(x) * parameter - death_probability.get(x))
+                / (1.0 + death_probability.get(x))
+        };
+
+        let mat = points
+            .par_iter()
+            .flat_map_iter(|&x| {
+                (0..dimension).map(move |k| {
+                    self.basis.evaluate(k, x, width)
+                        - self.integrator.integrate(
+                            &|y: f64| kernel(x, y) * self.basis.evaluate(k, y, width),
+                            0.0,
+                            width,
+                        )
+                })
+            })
+            .collect::<Vec<f64>>();
+        let b = points.par_iter().map(|&x| f(x)).collect::<Vec<f64>>();
+        check_finite(&mat, dimension)?;
+        check_finite(&b, 1)?;
+
+        let coefficients = self.equation_solver.solve(&mat, dimension, &b)?;
+
+        Ok(self.basis.function(coefficients, 1.0, width))
+    }
+
+    fn even_solution(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        basis::{BSplineBasis, OrthogonalPolynomialBasis, PolynomialFamily},
+        integrals::{GaussLegendreIntegrator, GradedMeshIntegrator},
+        linear_equation::LUSolver,
+    };
+
+    fn exponent_problem() -> (impl Function, impl Function, f64) {
+        let q = |x: f64| x * x / 3.0 - 16.0 / 9.0 * x.abs() + 56.0 / 27.0 + 1.0 / 3.0;
+        let m = |x: f64| (-2.0 * x.abs()).exp();
+        let w = move |x: f64| (-x.abs()).exp() * q(x) / (1.0 + (-x.abs()).exp() * (x * x + 1.0));
+        (m, w, 2.0 / 3.0 + 52.0 / 27.0)
+    }
+
+    /// Largest error against the exponent problem's solution on `[0, 10]`.
+    fn exponent_error(method: &dyn Method) -> f64 {
+        let (m, w, parameter) = exponent_problem();
+        let c = |x: f64| 1.0 + (-x.abs()).exp() * (x * x + 1.0);
+        let solution = method.solve(&m, &w, parameter, 10.0).unwrap();
+        (0..=200)
+            .map(|i| (solution.get(i as f64 / 20.0) - c(i as f64 / 20.0)).abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn graded_hats_converge_on_the_exponent_problem() {
+        let error = |elements| {
+            exponent_error(&CollocationMethod::new(
+                Box::new(GradedMeshIntegrator::new(4, 4 * elements, 2.0)),
+                Box::new(LUSolver),
+                BSplineBasis::hat(elements, 2.0),
+                CollocationPoints::Graded(2.0),
+            ))
+        };
+        let (coarse, fine) = (error(20), error(40));
+        assert!(coarse < 0.05, "{coarse}");
+        // Piecewise linear interpolation is second order.
+        assert!(fine < 0.3 * coarse, "{fine}");
+    }
+
+    #[test]
+    fn chebyshev_points_are_the_nonnegative_half() {
+        let points = CollocationPoints::Chebyshev.points(3, 2.0);
+        let all: Vec<f64> = (0..6)
+            .map(|i| 2.0 * ((2 * i + 1) as f64 * std::f64::consts::PI / 12.0).cos())
+            .collect();
+        for (point, expected) in points.iter().zip(&all[..3]) {
+            assert!((point - expected).abs() < 1e-15);
+        }
+        assert_eq!(
+            CollocationPoints::Graded(2.0).points(3, 4.0),
+            [0.0, 1.0, 4.0]
+        );
+    }
+
+    #[test]
+    fn custom_points_must_match_the_dimension() {
+        let (m, w, parameter) = exponent_problem();
+        let method = CollocationMethod::new(
+            Box::new(GaussLegendreIntegrator::composite(8, 4)),
+            Box::new(LUSolver),
+            OrthogonalPolynomialBasis::new(8, PolynomialFamily::Legendre),
+            CollocationPoints::Custom(vec![0.0, 1.0]),
+        );
+        assert!(matches!(
+            method.solve(&m, &w, parameter, 10.0),
+            Err(SolveError::DimensionMismatch {
+                expected: 4,
+                found: 2
+            })
+        ));
+    }
+}
@@ -0,0 +1,112 @@
+use rayon::prelude::*;
+
+use crate::{
+    functions::Function,
+    method::{check_finite, SolveError},
+    toeplitz::ToeplitzPlusHankel,
+};
+
+/// The integral operator `(K u)(x) = ∫(m(y - x) + m(-y - x)) u(y) dy / (1 + w(x))` over
+/// `[0, width]`, folded by evenness, discretized by the trapezoid rule on `node_count`
+/// equally spaced nodes. The folded kernel is a Toeplitz-plus-Hankel matrix, so it is
+/// applied by zero-padded FFTs in `O(n log n)`.
+pub struct ConvolutionOperator {
+    kernel: ToeplitzPlusHankel,
+    weights: Vec<f64>,
+    scale: Vec<f64>,
+}
+
+impl ConvolutionOperator {
+    pub fn new(
+        birth_probability: &dyn Function,
+        death_probability: &dyn Function,
+        node_count: usize,
+        width: f64,
+    ) -> Result<Self, SolveError> {
+        if !width.is_finite() {
+            return Err(SolveError::UnboundedDomain);
+        }
+        let n = node_count;
+        let step = width / (n - 1) as f64;
+
+        let kernel = ToeplitzPlusHankel::new(
+            n,
+            |d| birth_probability.get(-(d as f64) * step),
+            |s| birth_probability.get(-(s as f64) * step),
+        );
+        let weights = (0..n)
+            .map(|i| {
+                if i == 0 || i == n - 1 {
+                    step / 2.0
+                } else {
+                    step
+                }
+            })
+            .collect();
+        let scale: Vec<f64> = (0..n)
+            .into_par_iter()
+            .map(|j| 1.0 / (1.0 + death_probability.get((j as f64) * step)))
+            .collect();
+        check_finite(&scale, 1)?;
+
+        Ok(Self {
+            kernel,
+            weights,
+            scale,
+        })
+    }
+
+    /// `K u` at the nodes, for `u` given at the nodes.
+    pub fn apply(&self, u: &[f64]) -> Vec<f64> {
+        let weighted: Vec<f64> = u.iter().zip(&self.weights).map(|(u, w)| u * w).collect();
+        self.kernel
+            .apply(&weighted)
+            .iter()
+            .zip(&self.scale)
+            .map(|(ku, s)| ku * s)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_the_folded_trapezoid_kernel() {
+        let (n, width) = (37, 6.0);
+        let step = width / (n - 1) as f64;
+        let m = |x: f64| (-2.0 * x.abs()).exp();
+        let w = |x: f64| 0.5 / (1.0 + x * x);
+        let u: Vec<f64> = (0..n).map(|i| (i as f64 * 0.3).sin() + 1.0).collect();
+
+        let applied = ConvolutionOperator::new(&m, &w, n, width)
+            .unwrap()
+            .apply(&u);
+        for (j, value) in applied.iter().enumerate() {
+            let x = j as f64 * step;
+            let dense: f64 = (0..n)
+                .map(|i| {
+                    let y = i as f64 * step;
+                    let weight = if i == 0 || i == n - 1 {
+                        step / 2.0
+                    } else {
+                        step
+                    };
+                    (m(y - x) + m(-y - x)) * weight * u[i]
+                })
+                .sum::<f64>()
+                / (1.0 + w(x));
+            assert!((value - dense).abs() < 1e-13, "{value} != {dense}");
+        }
+    }
+
+    #[test]
+    fn rejects_infinite_widths() {
+        let m = |x: f64| (-x.abs()).exp();
+        assert!(matches!(
+            ConvolutionOperator::new(&m, &m, 10, f64::INFINITY),
+            Err(SolveError::UnboundedDomain)
+        ));
+    }
+}
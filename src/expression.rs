@@ -0,0 +1,514 @@
+use std::fmt;
+
+use crate::functions::{Function, Function2d};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionError {
+    /// A character that starts no token, at the given byte offset.
+    UnexpectedCharacter { position: usize, found: char },
+    /// A token where something else was expected.
+    UnexpectedToken { position: usize, found: String },
+    /// The expression ended in the middle.
+    UnexpectedEnd,
+    /// A name that is neither a variable, a parameter nor `pi`.
+    UnknownName { position: usize, name: String },
+    /// A call of a function that does not exist.
+    UnknownFunction { position: usize, name: String },
+    /// A parameter value that is NaN or infinite.
+    NonFiniteParameter { name: String },
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpressionError::UnexpectedCharacter { position, found } => {
+                write!(f, "unexpected character '{found}' at {position}")
+            }
+            ExpressionError::UnexpectedToken { position, found } => {
+                write!(f, "unexpected '{found}' at {position}")
+            }
+            ExpressionError::UnexpectedEnd => write!(f, "unexpected end of expression"),
+            ExpressionError::UnknownName { position, name } => {
+                write!(f, "unknown name '{name}' at {position}")
+            }
+            ExpressionError::UnknownFunction { position, name } => {
+                write!(f, "unknown function '{name}' at {position}")
+            }
+            ExpressionError::NonFiniteParameter { name } => {
+                write!(f, "parameter '{name}' is not finite")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ExpressionError> {
+    let mut tokens = vec![];
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let bytes = source.as_bytes();
+            let digit_at = |i: usize| bytes.get(i).is_some_and(u8::is_ascii_digit);
+            let mut end = position;
+            while digit_at(end) || bytes.get(end) == Some(&b'.') {
+                end += 1;
+            }
+            // `e` only starts an exponent if digits follow, so `2e` is `2` and a name.
+            if matches!(bytes.get(end), Some(b'e' | b'E')) {
+                let sign = usize::from(matches!(bytes.get(end + 1), Some(b'+' | b'-')));
+                if digit_at(end + 1 + sign) {
+                    end += 1 + sign;
+                    while digit_at(end) {
+                        end += 1;
+                    }
+                }
+            }
+            while chars.next_if(|&(i, _)| i < end).is_some() {}
+            let text = &source[position..end];
+            let number = text.parse().map_err(|_| ExpressionError::UnexpectedToken {
+                position,
+                found: text.to_string(),
+            })?;
+            tokens.push((position, Token::Number(number)));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                name.push(c);
+                chars.next();
+            }
+            tokens.push((position, Token::Name(name)));
+        } else if "+-*/^()".contains(c) {
+            tokens.push((position, Token::Symbol(c)));
+            chars.next();
+        } else {
+            return Err(ExpressionError::UnexpectedCharacter { position, found: c });
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Call {
+    Abs,
+    Exp,
+    Ln,
+    Sqrt,
+    Sin,
+    Cos,
+}
+
+impl Call {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "abs" => Some(Call::Abs),
+            "exp" => Some(Call::Exp),
+            "ln" => Some(Call::Ln),
+            "sqrt" => Some(Call::Sqrt),
+            "sin" => Some(Call::Sin),
+            "cos" => Some(Call::Cos),
+            _ => None,
+        }
+    }
+
+    fn apply(self, x: f64) -> f64 {
+        match self {
+            Call::Abs => x.abs(),
+            Call::Exp => x.exp(),
+            Call::Ln => x.ln(),
+            Call::Sqrt => x.sqrt(),
+            Call::Sin => x.sin(),
+            Call::Cos => x.cos(),
+        }
+    }
+}
+
+enum Node {
+    Constant(f64),
+    Variable(usize),
+    Negate(Box<Node>),
+    Binary(char, Box<Node>, Box<Node>),
+    Call(Call, Box<Node>),
+}
+
+fn binary(op: char, a: f64, b: f64) -> f64 {
+    match op {
+        '+' => a + b,
+        '-' => a - b,
+        '*' => a * b,
+        '/' => a / b,
+        _ => a.powf(b),
+    }
+}
+
+/// Recursive descent over
+/// `sum = product (('+' | '-') product)*`, `product = unary (('*' | '/') unary)*`,
+/// `unary = ('-' | '+') unary | power`, `power = atom ('^' unary)?`, so `-x^2` is `-(x^2)` and
+/// `^` associates to the right.
+struct Parser<'a> {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    variables: &'a [&'a str],
+    parameters: &'a [(&'a str, f64)],
+}
+
+impl Parser<'_> {
+    fn peek_symbol(&self) -> Option<char> {
+        match self.tokens.get(self.next) {
+            Some((_, Token::Symbol(c))) => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn unexpected(&self) -> ExpressionError {
+        match self.tokens.get(self.next) {
+            Some((position, token)) => ExpressionError::UnexpectedToken {
+                position: *position,
+                found: match token {
+                    Token::Number(number) => number.to_string(),
+                    Token::Name(name) => name.clone(),
+                    Token::Symbol(c) => c.to_string(),
+                },
+            },
+            None => ExpressionError::UnexpectedEnd,
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), ExpressionError> {
+        if self.peek_symbol() == Some(symbol) {
+            self.next += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn sum(&mut self) -> Result<Node, ExpressionError> {
+        let mut node = self.product()?;
+        while let Some(op @ ('+' | '-')) = self.peek_symbol() {
+            self.next += 1;
+            node = fold_binary(op, node, self.product()?);
+        }
+        Ok(node)
+    }
+
+    fn product(&mut self) -> Result<Node, ExpressionError> {
+        let mut node = self.unary()?;
+        while let Some(op @ ('*' | '/')) = self.peek_symbol() {
+            self.next += 1;
+            node = fold_binary(op, node, self.unary()?);
+        }
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> Result<Node, ExpressionError> {
+        if self.peek_symbol() == Some('+') {
+            self.next += 1;
+            return self.unary();
+        }
+        if self.peek_symbol() == Some('-') {
+            self.next += 1;
+            return Ok(match self.unary()? {
+                Node::Constant(value) => Node::Constant(-value),
+                node => Node::Negate(Box::new(node)),
+            });
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Node, ExpressionError> {
+        let base = self.atom()?;
+        if self.peek_symbol() == Some('^') {
+            self.next += 1;
+            return Ok(fold_binary('^', base, self.unary()?));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Node, ExpressionError> {
+        let Some((position, token)) = self.tokens.get(self.next).cloned() else {
+            return Err(ExpressionError::UnexpectedEnd);
+        };
+        match token {
+            Token::Number(number) => {
+                self.next += 1;
+                Ok(Node::Constant(number))
+            }
+            Token::Symbol('(') => {
+                self.next += 1;
+                let node = self.sum()?;
+                self.expect(')')?;
+                Ok(node)
+            }
+            Token::Name(name) => {
+                self.next += 1;
+                if self.peek_symbol() == Some('(') {
+                    let call = Call::from_name(&name)
+                        .ok_or(ExpressionError::UnknownFunction { position, name })?;
+                    self.next += 1;
+                    let argument = self.sum()?;
+                    self.expect(')')?;
+                    return Ok(match argument {
+                        Node::Constant(value) => Node::Constant(call.apply(value)),
+                        node => Node::Call(call, Box::new(node)),
+                    });
+                }
+                if let Some(i) = self.variables.iter().position(|&v| v == name) {
+                    Ok(Node::Variable(i))
+                } else if let Some(&(_, value)) = self.parameters.iter().find(|&&(p, _)| p == name)
+                {
+                    Ok(Node::Constant(value))
+                } else if name == "pi" {
+                    Ok(Node::Constant(std::f64::consts::PI))
+                } else if Call::from_name(&name).is_some() {
+                    // A function name without its parenthesized argument.
+                    Err(self.unexpected())
+                } else {
+                    Err(ExpressionError::UnknownName { position, name })
+                }
+            }
+            Token::Symbol(_) => Err(self.unexpected()),
+        }
+    }
+}
+
+fn fold_binary(op: char, a: Node, b: Node) -> Node {
+    match (a, b) {
+        (Node::Constant(a), Node::Constant(b)) => Node::Constant(binary(op, a, b)),
+        (a, b) => Node::Binary(op, Box::new(a), Box::new(b)),
+    }
+}
+
+type Compiled = Box<dyn Fn(&[f64]) -> f64 + Send + Sync>;
+
+/// Turns the tree into nested closures, so evaluation does no matching on node kinds.
+fn compile(node: Node) -> Compiled {
+    match node {
+        Node::Constant(value) => Box::new(move |_| value),
+        Node::Variable(i) => Box::new(move |variables| variables[i]),
+        Node::Negate(a) => {
+            let a = compile(*a);
+            Box::new(move |variables| -a(variables))
+        }
+        Node::Call(call, a) => {
+            let a = compile(*a);
+            Box::new(move |variables| call.apply(a(variables)))
+        }
+        Node::Binary('^', a, b) => {
+            let a = compile(*a);
+            match *b {
+                Node::Constant(n) if n.fract() == 0.0 && n.abs() <= i32::MAX as f64 => {
+                    Box::new(move |variables| a(variables).powi(n as i32))
+                }
+                b => {
+                    let b = compile(b);
+                    Box::new(move |variables| a(variables).powf(b(variables)))
+                }
+            }
+        }
+        Node::Binary(op, a, b) => {
+            let (a, b) = (compile(*a), compile(*b));
+            match op {
+                '+' => Box::new(move |variables| a(variables) + b(variables)),
+                '-' => Box::new(move |variables| a(variables) - b(variables)),
+                '*' => Box::new(move |variables| a(variables) * b(variables)),
+                _ => Box::new(move |variables| a(variables) / b(variables)),
+            }
+        }
+    }
+}
+
+/// An arithmetic expression with `+ - * / ^`, parentheses, the functions `abs`, `exp`,
+/// `ln`, `sqrt`, `sin`, `cos` and the constant `pi`, compiled once for fast evaluation.
+/// Parameters are substituted while parsing and constant parts folded.
+pub struct Expression {
+    compiled: Compiled,
+    variable_count: usize,
+}
+
+impl Expression {
+    /// `variables` are the names of the arguments of `evaluate`, in order, and shadow
+    /// `parameters` of the same name.
+    pub fn parse(
+        source: &str,
+        variables: &[&str],
+        parameters: &[(&str, f64)],
+    ) -> Result<Self, ExpressionError> {
+        if let Some(&(name, _)) = parameters.iter().find(|(_, value)| !value.is_finite()) {
+            return Err(ExpressionError::NonFiniteParameter {
+                name: name.to_string(),
+            });
+        }
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            next: 0,
+            variables,
+            parameters,
+        };
+        let node = parser.sum()?;
+        if parser.next < parser.tokens.len() {
+            return Err(parser.unexpected());
+        }
+
+        Ok(Self {
+            compiled: compile(node),
+            variable_count: variables.len(),
+        })
+    }
+
+    /// Panics if not given as many values as there are variables.
+    pub fn evaluate(&self, variables: &[f64]) -> f64 {
+        assert_eq!(variables.len(), self.variable_count);
+        (self.compiled)(variables)
+    }
+}
+
+/// An `Expression` of `x`.
+pub struct ExpressionFunction {
+    expression: Expression,
+}
+
+impl ExpressionFunction {
+    pub fn parse(source: &str, parameters: &[(&str, f64)]) -> Result<Self, ExpressionError> {
+        Ok(Self {
+            expression: Expression::parse(source, &["x"], parameters)?,
+        })
+    }
+}
+
+impl Function for ExpressionFunction {
+    fn get(&self, x: f64) -> f64 {
+        (self.expression.compiled)(&[x])
+    }
+}
+
+/// An `Expression` of `x` and `y`.
+pub struct ExpressionFunction2d {
+    expression: Expression,
+}
+
+impl ExpressionFunction2d {
+    pub fn parse(source: &str, parameters: &[(&str, f64)]) -> Result<Self, ExpressionError> {
+        Ok(Self {
+            expression: Expression::parse(source, &["x", "y"], parameters)?,
+        })
+    }
+}
+
+impl Function2d for ExpressionFunction2d {
+    fn get(&self, x: f64, y: f64) -> f64 {
+        (self.expression.compiled)(&[x, y])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(source: &str, x: f64) -> f64 {
+        Expression::parse(source, &["x"], &[("a", 2.0)])
+            .unwrap()
+            .evaluate(&[x])
+    }
+
+    fn error(source: &str) -> ExpressionError {
+        Expression::parse(source, &["x"], &[]).err().unwrap()
+    }
+
+    #[test]
+    fn precedence_and_associativity() {
+        assert_eq!(value("1 + 2 * 3 - 4 / 2", 0.0), 5.0);
+        assert_eq!(value("(1 + 2) * 3", 0.0), 9.0);
+        assert_eq!(value("2 * x^2", 3.0), 18.0);
+        assert_eq!(value("2^3^2", 0.0), 512.0);
+        assert_eq!(value("x^a^2", 2.0), 16.0);
+        assert_eq!(value("8 / 4 / 2", 0.0), 1.0);
+        assert_eq!(value("a * exp(0) + sqrt(x)", 9.0), 5.0);
+    }
+
+    #[test]
+    fn unary_signs() {
+        assert_eq!(value("-x^2", 3.0), -9.0);
+        assert_eq!(value("2^-1", 0.0), 0.5);
+        assert_eq!(value("- -x", 3.0), 3.0);
+        assert_eq!(value("+x", 3.0), 3.0);
+        assert_eq!(value("2 * +x - -1", 3.0), 7.0);
+        assert_eq!(value("(+1)", 0.0), 1.0);
+    }
+
+    #[test]
+    fn exponents_need_digits() {
+        assert_eq!(value("2e3", 0.0), 2000.0);
+        assert_eq!(value("1.5E-2 * x", 2.0), 0.03);
+        assert_eq!(value("2e+1", 0.0), 20.0);
+        assert_eq!(value("x-2e-1", 1.0), 0.8);
+        assert_eq!(
+            error("2e"),
+            ExpressionError::UnexpectedToken {
+                position: 1,
+                found: "e".to_string()
+            }
+        );
+        assert_eq!(
+            error("2e-x"),
+            ExpressionError::UnexpectedToken {
+                position: 1,
+                found: "e".to_string()
+            }
+        );
+        assert_eq!(
+            error("2exp(x)"),
+            ExpressionError::UnexpectedToken {
+                position: 1,
+                found: "exp".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(
+            error("1 + $"),
+            ExpressionError::UnexpectedCharacter {
+                position: 4,
+                found: '$'
+            }
+        );
+        assert_eq!(error("1 + (2"), ExpressionError::UnexpectedEnd);
+        assert_eq!(
+            error("1 + y"),
+            ExpressionError::UnknownName {
+                position: 4,
+                name: "y".to_string()
+            }
+        );
+        assert_eq!(
+            error("x * foo(1)"),
+            ExpressionError::UnknownFunction {
+                position: 4,
+                name: "foo".to_string()
+            }
+        );
+        assert_eq!(
+            error("(1 + 2))"),
+            ExpressionError::UnexpectedToken {
+                position: 7,
+                found: ")".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, rhs: Complex) -> Complex {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, rhs: Complex) -> Complex {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+/// In-place radix-2 FFT, `data.len()` must be a power of two.
+/// The inverse transform is scaled by `1 / data.len()`.
+pub fn fft(data: &mut [Complex], inverse: bool) {
+    let n = data.len();
+    assert!(n.is_power_of_two(), "fft length must be a power of two");
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let angle = sign * 2.0 * PI / len as f64;
+        let root = Complex::new(angle.cos(), angle.sin());
+        for chunk in data.chunks_mut(len) {
+            let mut w = Complex::new(1.0, 0.0);
+            for k in 0..len / 2 {
+                let a = chunk[k];
+                let b = chunk[k + len / 2] * w;
+                chunk[k] = a + b;
+                chunk[k + len / 2] = a - b;
+                w = w * root;
+            }
+        }
+        len <<= 1;
+    }
+
+    if inverse {
+        let scale = 1.0 / n as f64;
+        for v in data.iter_mut() {
+            v.re *= scale;
+            v.im *= scale;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_direct_dft_and_round_trips() {
+        let n = 16;
+        let data: Vec<Complex> = (0..n)
+            .map(|i| Complex::new((i as f64 * 0.7).sin(), 1.0 / (1.0 + i as f64)))
+            .collect();
+
+        let mut transformed = data.clone();
+        fft(&mut transformed, false);
+        for (k, value) in transformed.iter().enumerate() {
+            let direct = data
+                .iter()
+                .enumerate()
+                .fold(Complex::default(), |acc, (j, &v)| {
+                    let angle = -2.0 * PI * (j * k) as f64 / n as f64;
+                    acc + v * Complex::new(angle.cos(), angle.sin())
+                });
+            assert!((value.re - direct.re).abs() < 1e-12);
+            assert!((value.im - direct.im).abs() < 1e-12);
+        }
+
+        fft(&mut transformed, true);
+        for (a, b) in transformed.iter().zip(&data) {
+            assert!((a.re - b.re).abs() < 1e-14);
+            assert!((a.im - b.im).abs() < 1e-14);
+        }
+    }
+}
@@ -1,5 +1,3 @@
-// use exmex::prelude::*;
-
 pub trait Function: Sync {
     fn get(&self, x: f64) -> f64;
     fn to_vec(&self, left: f64, right: f64, node_count: usize) -> Vec<(f64, f64)> {
@@ -13,12 +11,6 @@ pub trait Function: Sync {
     }
 }
 
-// impl Function for exmex::FlatEx<f64> {
-//     fn get(&self, x: f64) -> f64 {
-//         self.eval(&[x]).unwrap()
-//     }
-// }
-
 pub trait Function2d: Sync {
     fn get(&self, x: f64, y: f64) -> f64;
 }
@@ -41,32 +33,19 @@ where
     }
 }
 
-pub struct PointFunction {
-    pts: Vec<f64>,
-    left: f64,
-    right: f64,
+/// `inner(|x|)`, extends a function known on `x >= 0` to an even function on the whole line.
+pub struct EvenFunction<'a> {
+    inner: Box<dyn Function + 'a>,
 }
 
-impl PointFunction {
-    pub fn new(pts: Vec<f64>, left: f64, right: f64) -> Self {
-        Self { pts, left, right }
+impl<'a> EvenFunction<'a> {
+    pub fn new(inner: Box<dyn Function + 'a>) -> Self {
+        Self { inner }
     }
 }
 
-impl Function for PointFunction {
+impl Function for EvenFunction<'_> {
     fn get(&self, x: f64) -> f64 {
-        if self.left > x {
-            return self.pts[0];
-        }
-
-        let step = (self.right - self.left) / (self.pts.len() - 1) as f64;
-        let i = ((x - self.left) / step).floor() as usize;
-        let t = ((x - self.left) / step).fract();
-
-        if i + 2 > self.pts.len() {
-            return self.pts.last().unwrap().to_owned();
-        }
-
-        self.pts[i] * t + self.pts[i + 1] * (1.0 - t)
+        self.inner.get(x.abs())
     }
 }
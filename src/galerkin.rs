@@ -1,115 +1,213 @@
 use crate::{
-    functions::Function, integrals::Integrator, linear_equation::LinearEquationSolver,
-    method::Method,
+    basis::Basis,
+    functions::Function,
+    integrals::Integrator,
+    linear_equation::LinearEquationSolver,
+    method::{check_finite, Method, SolveError},
 };
 
 use rayon::prelude::*;
 
-pub struct GalerkinMethod {
+pub struct Galerkin<B: Basis> {
     integrator: Box<dyn Integrator>,
     equation_solver: Box<dyn LinearEquationSolver>,
-    weight_func: Box<dyn Function>,
-
-    polynome_degree: usize,
+    weight_func: Option<Box<dyn Function>>,
+    basis: B,
 }
 
-impl GalerkinMethod {
+impl<B: Basis> Galerkin<B> {
+    /// `weight_func` is evaluated at `x / width` and weighs the projection, it has to be even.
     pub fn new(
         integrator: Box<dyn Integrator>,
         equation_solver: Box<dyn LinearEquationSolver>,
         weight_func: Box<dyn Function>,
-        polynome_degree: usize,
+        basis: B,
     ) -> Self {
         Self {
             integrator,
             equation_solver,
-            weight_func,
-            polynome_degree,
+            weight_func: Some(weight_func),
+            basis,
         }
     }
-}
-
-struct EvenPowerPolynomial {
-    coefficients: Vec<f64>,
-}
-
-impl EvenPowerPolynomial {
-    fn new(coefficients: Vec<f64>) -> Self {
-        Self { coefficients }
-    }
-}
 
-impl Function for EvenPowerPolynomial {
-    fn get(&self, x: f64) -> f64 {
-        (0..self.coefficients.len())
-            .map(|n| (x).powf((n * 2) as f64) * self.coefficients[n])
-            .sum()
+    /// Projects with unit weight, so the closed form Gram entries of the basis are used
+    /// when it has them.
+    pub fn unweighted(
+        integrator: Box<dyn Integrator>,
+        equation_solver: Box<dyn LinearEquationSolver>,
+        basis: B,
+    ) -> Self {
+        Self {
+            integrator,
+            equation_solver,
+            weight_func: None,
+            basis,
+        }
     }
 }
 
-impl Method for GalerkinMethod {
-    fn solve(
-        &self,
-        birth_probability: &dyn Function,
-        death_probability: &dyn Function,
+impl<B: Basis> Method for Galerkin<B> {
+    fn solve<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
         parameter: f64,
         width: f64,
-    ) -> Box<dyn Function> {
-        let h = |t: f64, x: f64| -birth_probability.get(t - x) / (1.0 + death_probability.get(x));
+    ) -> Result<Box<dyn Function + 'a>, SolveError> {
+        if !width.is_finite() {
+            return Err(SolveError::UnboundedDomain);
+        }
+        // Every integrand is even, so the integrals are folded onto `[0, width]`.
+        let h = |t: f64, x: f64| {
+            -(birth_probability.get(t - x) + birth_probability.get(-t - x))
+                / (1.0 + death_probability.get(x))
+        };
         let y = |x: f64| {
             (birth_probability.get(x) * parameter - death_probability.get(x))
                 / (1.0 + death_probability.get(x))
         };
 
-        let base = |t: f64, n: usize| t.powi(n as i32 * 2);
+        let weight = |x: f64| match &self.weight_func {
+            Some(weight_func) => weight_func.get((x / width).clamp(-1.0, 1.0)),
+            None => 1.0,
+        };
+        let base = |t: f64, n: usize| self.basis.evaluate(n, t, width);
+        let gram = |j: usize, k: usize| {
+            self.weight_func
+                .is_none()
+                .then(|| self.basis.gram(j, k, width))
+                .flatten()
+                .unwrap_or_else(|| {
+                    2.0 * self.integrator.integrate(
+                        &|x: f64| weight(x) * base(x, j) * base(x, k),
+                        0.0,
+                        width,
+                    )
+                })
+        };
         let ajk = |j: usize, k: usize| {
-            self.integrator.integrate(
-                &|x: f64| {
-                    self.weight_func.get((x / width).clamp(-1.0, 1.0))
-                        * base(x, j)
-                        * (base(x, k)
-                            + self.integrator.integrate(
-                                &|t: f64| h(t, x) * base(t, k),
-                                -width,
-                                width,
-                            ))
-                },
-                -width,
-                width,
-            )
+            gram(j, k)
+                + 2.0
+                    * self.integrator.integrate(
+                        &|x: f64| {
+                            // Skips the inner integral where a local basis function vanishes.
+                            let outer = weight(x) * base(x, j);
+                            if outer == 0.0 {
+                                return 0.0;
+                            }
+                            outer
+                                * self.integrator.integrate(
+                                    &|t: f64| h(t, x) * base(t, k),
+                                    0.0,
+                                    width,
+                                )
+                        },
+                        0.0,
+                        width,
+                    )
         };
 
         let bj = |j: usize| {
-            self.integrator.integrate(
-                &|x: f64| self.weight_func.get((x / width).clamp(-1.0, 1.0)) * y(x) * base(x, j),
-                -width,
-                width,
-            )
+            2.0 * self
+                .integrator
+                .integrate(&|x: f64| weight(x) * y(x) * base(x, j), 0.0, width)
         };
 
-        let mat = (0..self.polynome_degree / 2)
+        let dimension = self.basis.dimension();
+        let mat = (0..dimension)
             .into_par_iter()
             .map(|j| {
-                (0..self.polynome_degree / 2)
+                (0..dimension)
                     .into_par_iter()
                     .map(|k| ajk(j, k))
                     .collect::<Vec<f64>>()
             })
             .flatten()
             .collect::<Vec<f64>>();
-        let b = (0..self.polynome_degree / 2)
-            .into_par_iter()
-            .map(|j| bj(j))
-            .collect::<Vec<f64>>();
+        let b = (0..dimension).into_par_iter().map(bj).collect::<Vec<f64>>();
+        check_finite(&mat, dimension)?;
+        check_finite(&b, 1)?;
+
+        let coefficients = self.equation_solver.solve(&mat, dimension, &b)?;
+
+        Ok(self.basis.function(coefficients, 1.0, width))
+    }
 
-        let mut coefficients = self
-            .equation_solver
-            .solve(&mat, self.polynome_degree / 2, &b)
-            .unwrap();
-        coefficients[0] += 1.0;
+    fn even_solution(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        basis::{BSplineBasis, EvenPowerBasis, OrthogonalPolynomialBasis, PolynomialFamily},
+        integrals::{GaussLegendreIntegrator, GradedMeshIntegrator},
+        linear_equation::LUSolver,
+    };
+
+    fn weighted<B: Basis>(basis: B) -> Galerkin<B> {
+        Galerkin::new(
+            Box::new(GaussLegendreIntegrator::composite(8, 20)),
+            Box::new(LUSolver),
+            Box::new(|t: f64| (1.0f64 - t.powi(2)).sqrt()),
+            basis,
+        )
+    }
 
-        //println!("{:?}\n{:?}\n{:?}", mat, b, coefficients);
+    /// Solves the exponent problem on `[-10, 10]` and samples the solution at `x = i / 10`.
+    fn solve_exponent(method: &dyn Method) -> Vec<f64> {
+        let q = |x: f64| x * x / 3.0 - 16.0 / 9.0 * x.abs() + 56.0 / 27.0 + 1.0 / 3.0;
+        let m = |x: f64| (-2.0 * x.abs()).exp();
+        let w = move |x: f64| (-x.abs()).exp() * q(x) / (1.0 + (-x.abs()).exp() * (x * x + 1.0));
+        let solution = method.solve(&m, &w, 2.0 / 3.0 + 52.0 / 27.0, 10.0).unwrap();
+        (0..=100).map(|i| solution.get(i as f64 / 10.0)).collect()
+    }
+
+    fn exponent_error(values: &[f64]) -> f64 {
+        let c = |x: f64| 1.0 + (-x.abs()).exp() * (x * x + 1.0);
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (v - c(i as f64 / 10.0)).abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn approaches_the_exponent_solution() {
+        // The solution has a kink at 0, so even polynomials converge slowly.
+        let legendre = |degree| {
+            exponent_error(&solve_exponent(&weighted(OrthogonalPolynomialBasis::new(
+                degree,
+                PolynomialFamily::Legendre,
+            ))))
+        };
+        let (coarse, fine) = (legendre(20), legendre(40));
+        assert!(coarse < 0.2, "{coarse}");
+        assert!(fine < 0.8 * coarse, "{fine}");
+    }
+
+    #[test]
+    fn bases_of_the_same_space_agree() {
+        let powers = solve_exponent(&weighted(EvenPowerBasis::new(20)));
+        for family in [PolynomialFamily::Legendre, PolynomialFamily::Chebyshev] {
+            let orthogonal = solve_exponent(&weighted(OrthogonalPolynomialBasis::new(20, family)));
+            for (p, o) in powers.iter().zip(&orthogonal) {
+                assert!((p - o).abs() < 1e-6, "{family:?}: {p} != {o}");
+            }
+        }
+    }
 
-        Box::new(EvenPowerPolynomial::new(coefficients))
+    #[test]
+    fn graded_splines_resolve_the_cusp() {
+        let spline = Galerkin::unweighted(
+            Box::new(GradedMeshIntegrator::new(4, 80, 2.0)),
+            Box::new(LUSolver),
+            BSplineBasis::cubic(20, 2.0),
+        );
+        let error = exponent_error(&solve_exponent(&spline));
+        assert!(error < 1e-2, "{error}");
     }
 }
@@ -1,9 +1,51 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
 use rayon::prelude::*;
 
 use crate::functions::Function;
 
+pub struct Integral {
+    pub value: f64,
+    /// Estimated absolute error, if the integrator can provide one.
+    pub error_estimate: Option<f64>,
+    pub evaluations: usize,
+}
+
+/// `left` may be `-∞` and `right` may be `+∞` only for integrators that say they accept it.
 pub trait Integrator: Sync {
     fn integrate(&self, f: &dyn Function, left: f64, right: f64) -> f64;
+
+    fn integrate_with_estimate(&self, f: &dyn Function, left: f64, right: f64) -> Integral {
+        let evaluations = AtomicUsize::new(0);
+        let value = self.integrate(
+            &|x: f64| {
+                evaluations.fetch_add(1, AtomicOrdering::Relaxed);
+                f.get(x)
+            },
+            left,
+            right,
+        );
+
+        Integral {
+            value,
+            error_estimate: None,
+            evaluations: evaluations.into_inner(),
+        }
+    }
+}
+
+/// A fixed rule that can hand out its nodes and weights, as the Nyström method needs them.
+pub trait QuadratureRule: Sync {
+    fn nodes_and_weights(&self, left: f64, right: f64) -> Vec<(f64, f64)>;
+}
+
+fn integrate_rule(rule: &dyn QuadratureRule, f: &dyn Function, left: f64, right: f64) -> f64 {
+    rule.nodes_and_weights(left, right)
+        .par_iter()
+        .map(|(x, w)| w * f.get(*x))
+        .sum()
 }
 
 pub struct TrapezoidIntegrator {
@@ -32,3 +74,784 @@ impl Integrator for TrapezoidIntegrator {
             / 2.0
     }
 }
+
+impl QuadratureRule for TrapezoidIntegrator {
+    fn nodes_and_weights(&self, left: f64, right: f64) -> Vec<(f64, f64)> {
+        let step = (right - left) / (self.node_count - 1) as f64;
+
+        (0..self.node_count)
+            .map(|i| {
+                let weight = if i == 0 || i == self.node_count - 1 {
+                    step / 2.0
+                } else {
+                    step
+                };
+                ((i as f64) * step + left, weight)
+            })
+            .collect()
+    }
+}
+
+pub struct SimpsonIntegrator {
+    node_count: usize,
+}
+
+impl SimpsonIntegrator {
+    /// An even number of intervals is needed, so an even `node_count` gets one more node.
+    pub fn new(node_count: usize) -> Self {
+        Self {
+            node_count: node_count | 1,
+        }
+    }
+}
+
+impl Integrator for SimpsonIntegrator {
+    fn integrate(&self, f: &dyn Function, left: f64, right: f64) -> f64 {
+        let step = (right - left) / (self.node_count - 1) as f64;
+
+        (0..(self.node_count - 1) / 2)
+            .into_par_iter()
+            .map(|i| {
+                let x = (2 * i) as f64 * step + left;
+                f.get(x) + 4.0 * f.get(x + step) + f.get(x + 2.0 * step)
+            })
+            .sum::<f64>()
+            * step
+            / 3.0
+    }
+}
+
+impl QuadratureRule for SimpsonIntegrator {
+    fn nodes_and_weights(&self, left: f64, right: f64) -> Vec<(f64, f64)> {
+        let step = (right - left) / (self.node_count - 1) as f64;
+
+        (0..self.node_count)
+            .map(|i| {
+                let weight = if i == 0 || i == self.node_count - 1 {
+                    1.0
+                } else if i % 2 == 1 {
+                    4.0
+                } else {
+                    2.0
+                };
+                ((i as f64) * step + left, weight * step / 3.0)
+            })
+            .collect()
+    }
+}
+
+/// Trapezoid rule on `1, 2, 4, ..., 2^(levels - 1)` intervals with Richardson extrapolation.
+pub struct RombergIntegrator {
+    levels: usize,
+}
+
+impl RombergIntegrator {
+    pub fn new(levels: usize) -> Self {
+        Self { levels }
+    }
+
+    /// Row `k` holds the trapezoid value on `2^k` intervals followed by its `k` extrapolations.
+    pub fn tableau(&self, f: &dyn Function, left: f64, right: f64) -> Vec<Vec<f64>> {
+        let mut tableau: Vec<Vec<f64>> =
+            vec![vec![(f.get(left) + f.get(right)) * (right - left) / 2.0]];
+
+        for k in 1..self.levels {
+            let intervals = 1usize << k;
+            let step = (right - left) / intervals as f64;
+            let midpoints = (0..intervals / 2)
+                .into_par_iter()
+                .map(|i| f.get(left + (2 * i + 1) as f64 * step))
+                .sum::<f64>();
+
+            let previous = &tableau[k - 1];
+            let mut row = vec![previous[0] / 2.0 + midpoints * step];
+            for j in 1..=k {
+                let factor = 4f64.powi(j as i32);
+                row.push((factor * row[j - 1] - previous[j - 1]) / (factor - 1.0));
+            }
+            tableau.push(row);
+        }
+
+        tableau
+    }
+
+    /// Observed convergence orders of the first tableau column,
+    /// `log2((R[k-1] - R[k-2]) / (R[k] - R[k-1]))`.
+    pub fn observed_orders(tableau: &[Vec<f64>]) -> Vec<f64> {
+        tableau
+            .windows(3)
+            .map(|rows| {
+                ((rows[1][0] - rows[0][0]) / (rows[2][0] - rows[1][0]))
+                    .abs()
+                    .log2()
+            })
+            .collect()
+    }
+}
+
+impl Integrator for RombergIntegrator {
+    fn integrate(&self, f: &dyn Function, left: f64, right: f64) -> f64 {
+        self.tableau(f, left, right)
+            .last()
+            .and_then(|row| row.last())
+            .copied()
+            .unwrap()
+    }
+}
+
+/// Gauss–Legendre rule of the given order, applied on `subintervals` equal pieces of the
+/// integration range, both are at least 1.
+pub struct GaussLegendreIntegrator {
+    nodes: Vec<f64>,
+    weights: Vec<f64>,
+    subintervals: usize,
+}
+
+impl GaussLegendreIntegrator {
+    pub fn new(order: usize) -> Self {
+        Self::composite(order, 1)
+    }
+
+    pub fn composite(order: usize, subintervals: usize) -> Self {
+        assert!(order >= 1, "Gauss–Legendre order must be at least 1");
+        assert!(
+            subintervals >= 1,
+            "Gauss–Legendre needs at least 1 subinterval"
+        );
+        let (nodes, weights) = gauss_legendre(order);
+        Self {
+            nodes,
+            weights,
+            subintervals,
+        }
+    }
+}
+
+/// Nodes and weights of the `order`-point Gauss–Legendre rule on `[-1, 1]`, found by
+/// Newton's method on the Legendre polynomial from the usual asymptotic first guess.
+pub fn gauss_legendre(order: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut nodes: Vec<f64> = (0..order).map(|_| 0.0).collect();
+    let mut weights = nodes.clone();
+    let n = order as f64;
+
+    for i in 0..order.div_ceil(2) {
+        let mut x = (std::f64::consts::PI * (i as f64 + 0.75) / (n + 0.5)).cos();
+        for _ in 0..100 {
+            let (p, dp) = legendre(order, x);
+            let dx = p / dp;
+            x -= dx;
+            if dx.abs() < 1e-15 {
+                break;
+            }
+        }
+
+        let (_, dp) = legendre(order, x);
+        let w = 2.0 / ((1.0 - x * x) * dp * dp);
+        nodes[i] = -x;
+        nodes[order - 1 - i] = x;
+        weights[i] = w;
+        weights[order - 1 - i] = w;
+    }
+
+    (nodes, weights)
+}
+
+/// `P_n(x)` and `P_n'(x)` by the three-term recurrence.
+fn legendre(n: usize, x: f64) -> (f64, f64) {
+    let (mut p0, mut p1) = (1.0, x);
+    if n == 0 {
+        return (1.0, 0.0);
+    }
+    for k in 2..=n {
+        let k = k as f64;
+        let p2 = ((2.0 * k - 1.0) * x * p1 - (k - 1.0) * p0) / k;
+        p0 = p1;
+        p1 = p2;
+    }
+    let n = n as f64;
+    (p1, n * (x * p1 - p0) / (x * x - 1.0))
+}
+
+impl Integrator for GaussLegendreIntegrator {
+    fn integrate(&self, f: &dyn Function, left: f64, right: f64) -> f64 {
+        let step = (right - left) / self.subintervals as f64;
+
+        (0..self.subintervals)
+            .into_par_iter()
+            .map(|i| {
+                let center = left + (i as f64 + 0.5) * step;
+                self.nodes
+                    .iter()
+                    .zip(&self.weights)
+                    .map(|(x, w)| w * f.get(center + x * step / 2.0))
+                    .sum::<f64>()
+                    * step
+                    / 2.0
+            })
+            .sum()
+    }
+}
+
+impl QuadratureRule for GaussLegendreIntegrator {
+    fn nodes_and_weights(&self, left: f64, right: f64) -> Vec<(f64, f64)> {
+        let step = (right - left) / self.subintervals as f64;
+
+        (0..self.subintervals)
+            .flat_map(|i| {
+                let center = left + (i as f64 + 0.5) * step;
+                self.nodes
+                    .iter()
+                    .zip(&self.weights)
+                    .map(move |(x, w)| (center + x * step / 2.0, w * step / 2.0))
+            })
+            .collect()
+    }
+}
+
+/// Clenshaw–Curtis rule on the `order + 1` Chebyshev extreme points, `order` is at least 1.
+pub struct ClenshawCurtisIntegrator {
+    nodes: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl ClenshawCurtisIntegrator {
+    pub fn new(order: usize) -> Self {
+        assert!(order >= 1, "Clenshaw–Curtis order must be at least 1");
+        let n = order as f64;
+        let (nodes, weights) = (0..=order)
+            .map(|k| {
+                let theta = k as f64 * std::f64::consts::PI / n;
+                let sum: f64 = (1..=order / 2)
+                    .map(|j| {
+                        let b = if 2 * j == order { 1.0 } else { 2.0 };
+                        b / (4.0 * (j * j) as f64 - 1.0) * (2.0 * j as f64 * theta).cos()
+                    })
+                    .sum();
+                let c = if k == 0 || k == order { 1.0 } else { 2.0 };
+                (-theta.cos(), c / n * (1.0 - sum))
+            })
+            .unzip();
+
+        Self { nodes, weights }
+    }
+}
+
+impl QuadratureRule for ClenshawCurtisIntegrator {
+    fn nodes_and_weights(&self, left: f64, right: f64) -> Vec<(f64, f64)> {
+        let center = (left + right) / 2.0;
+        let half = (right - left) / 2.0;
+
+        self.nodes
+            .iter()
+            .zip(&self.weights)
+            .map(|(x, w)| (center + half * x, half * w))
+            .collect()
+    }
+}
+
+impl Integrator for ClenshawCurtisIntegrator {
+    fn integrate(&self, f: &dyn Function, left: f64, right: f64) -> f64 {
+        integrate_rule(self, f, left, right)
+    }
+}
+
+/// Gauss–Legendre rule of the given order on each piece of a mesh graded towards `left`,
+/// the breaks are at `left + (right - left) (i / intervals)^grading`.
+pub struct GradedMeshIntegrator {
+    nodes: Vec<f64>,
+    weights: Vec<f64>,
+    intervals: usize,
+    grading: f64,
+}
+
+impl GradedMeshIntegrator {
+    pub fn new(order: usize, intervals: usize, grading: f64) -> Self {
+        let (nodes, weights) = gauss_legendre(order);
+        Self {
+            nodes,
+            weights,
+            intervals,
+            grading,
+        }
+    }
+}
+
+impl QuadratureRule for GradedMeshIntegrator {
+    fn nodes_and_weights(&self, left: f64, right: f64) -> Vec<(f64, f64)> {
+        let point = |i: usize| {
+            left + (right - left) * (i as f64 / self.intervals as f64).powf(self.grading)
+        };
+
+        (0..self.intervals)
+            .flat_map(|i| {
+                let (a, b) = (point(i), point(i + 1));
+                self.nodes
+                    .iter()
+                    .zip(&self.weights)
+                    .map(move |(x, w)| ((a + b) / 2.0 + x * (b - a) / 2.0, w * (b - a) / 2.0))
+            })
+            .collect()
+    }
+}
+
+impl Integrator for GradedMeshIntegrator {
+    fn integrate(&self, f: &dyn Function, left: f64, right: f64) -> f64 {
+        integrate_rule(self, f, left, right)
+    }
+}
+
+const KRONROD_NODES: [f64; 8] = [
+    0.991_455_371_120_812_6,
+    0.949_107_912_342_758_5,
+    0.864_864_423_359_769_1,
+    0.741_531_185_599_394_4,
+    0.586_087_235_467_691_1,
+    0.405_845_151_377_397_2,
+    0.207_784_955_007_898_5,
+    0.0,
+];
+const KRONROD_WEIGHTS: [f64; 8] = [
+    0.022_935_322_010_529_22,
+    0.063_092_092_629_978_55,
+    0.104_790_010_322_250_18,
+    0.140_653_259_715_525_92,
+    0.169_004_726_639_267_9,
+    0.190_350_578_064_785_4,
+    0.204_432_940_075_298_9,
+    0.209_482_141_084_727_83,
+];
+// Weights of the embedded 7-point Gauss rule at KRONROD_NODES[1], [3], [5] and [7].
+const GAUSS_WEIGHTS: [f64; 4] = [
+    0.129_484_966_168_869_7,
+    0.279_705_391_489_276_7,
+    0.381_830_050_505_118_9,
+    0.417_959_183_673_469_4,
+];
+
+/// Adaptive 7-point Gauss / 15-point Kronrod integrator. The piece with the largest error
+/// estimate is bisected until the total estimate drops below
+/// `max(absolute_tolerance, relative_tolerance * |value|)` or `max_subdivisions` is reached.
+pub struct GaussKronrodIntegrator {
+    absolute_tolerance: f64,
+    relative_tolerance: f64,
+    max_subdivisions: usize,
+}
+
+impl GaussKronrodIntegrator {
+    pub fn new(absolute_tolerance: f64, relative_tolerance: f64, max_subdivisions: usize) -> Self {
+        Self {
+            absolute_tolerance,
+            relative_tolerance,
+            max_subdivisions,
+        }
+    }
+}
+
+struct Piece {
+    left: f64,
+    right: f64,
+    value: f64,
+    error: f64,
+}
+
+impl Piece {
+    fn new(f: &dyn Function, left: f64, right: f64) -> Self {
+        let center = (left + right) / 2.0;
+        let half = (right - left) / 2.0;
+
+        let mut kronrod = 0.0;
+        let mut gauss = 0.0;
+        for (i, (x, w)) in KRONROD_NODES.iter().zip(KRONROD_WEIGHTS).enumerate() {
+            let values = if *x == 0.0 {
+                f.get(center)
+            } else {
+                f.get(center - half * x) + f.get(center + half * x)
+            };
+            kronrod += w * values;
+            if i % 2 == 1 {
+                gauss += GAUSS_WEIGHTS[i / 2] * values;
+            }
+        }
+
+        Self {
+            left,
+            right,
+            value: kronrod * half,
+            error: ((kronrod - gauss) * half).abs(),
+        }
+    }
+}
+
+impl PartialEq for Piece {
+    fn eq(&self, other: &Self) -> bool {
+        self.error == other.error
+    }
+}
+
+impl Eq for Piece {}
+
+impl PartialOrd for Piece {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Piece {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.error.total_cmp(&other.error)
+    }
+}
+
+impl Integrator for GaussKronrodIntegrator {
+    fn integrate(&self, f: &dyn Function, left: f64, right: f64) -> f64 {
+        self.integrate_with_estimate(f, left, right).value
+    }
+
+    fn integrate_with_estimate(&self, f: &dyn Function, left: f64, right: f64) -> Integral {
+        let first = Piece::new(f, left, right);
+        let mut value = first.value;
+        let mut error = first.error;
+        let mut evaluations = 15;
+
+        let mut pieces = BinaryHeap::new();
+        pieces.push(first);
+
+        for _ in 0..self.max_subdivisions {
+            if error
+                <= self
+                    .absolute_tolerance
+                    .max(self.relative_tolerance * value.abs())
+            {
+                break;
+            }
+
+            let worst = pieces.pop().unwrap();
+            let center = (worst.left + worst.right) / 2.0;
+            let a = Piece::new(f, worst.left, center);
+            let b = Piece::new(f, center, worst.right);
+            evaluations += 30;
+
+            value += a.value + b.value - worst.value;
+            error += a.error + b.error - worst.error;
+            pieces.push(a);
+            pieces.push(b);
+        }
+
+        // Recompute the sums to drop the round-off accumulated by the updates above.
+        Integral {
+            value: pieces.iter().map(|p| p.value).sum(),
+            error_estimate: Some(pieces.iter().map(|p| p.error).sum()),
+            evaluations,
+        }
+    }
+}
+
+/// Maps `t` to the node `x` and the derivative `dx/dt`.
+type Transform = Box<dyn Fn(f64) -> (f64, f64)>;
+
+/// Double exponential quadrature with step `step` in the transformed variable `t`:
+/// tanh-sinh on finite intervals, exp-sinh on half-lines and sinh-sinh on the whole line.
+/// Accepts infinite bounds.
+pub struct TanhSinhIntegrator {
+    step: f64,
+}
+
+impl TanhSinhIntegrator {
+    pub fn new(step: f64) -> Self {
+        Self { step }
+    }
+}
+
+impl QuadratureRule for TanhSinhIntegrator {
+    /// Terms whose node falls on an end or whose weight under- or overflows are dropped.
+    fn nodes_and_weights(&self, left: f64, right: f64) -> Vec<(f64, f64)> {
+        let half_pi = std::f64::consts::FRAC_PI_2;
+        let (t_max, transform): (f64, Transform) = match (left.is_finite(), right.is_finite()) {
+            (true, true) => {
+                let half = (right - left) / 2.0;
+                (
+                    3.5,
+                    Box::new(move |t: f64| {
+                        let u = half_pi * t.sinh();
+                        // Distance to the nearest end, kept accurate near the ends.
+                        let distance = half * 2.0 / (1.0 + (2.0 * u.abs()).exp());
+                        let x = if u < 0.0 {
+                            left + distance
+                        } else {
+                            right - distance
+                        };
+                        (x, half * half_pi * t.cosh() / u.cosh().powi(2))
+                    }),
+                )
+            }
+            (true, false) => (
+                4.0,
+                Box::new(move |t: f64| {
+                    let e = (half_pi * t.sinh()).exp();
+                    (left + e, half_pi * t.cosh() * e)
+                }),
+            ),
+            (false, true) => (
+                4.0,
+                Box::new(move |t: f64| {
+                    let e = (half_pi * t.sinh()).exp();
+                    (right - e, half_pi * t.cosh() * e)
+                }),
+            ),
+            (false, false) => (
+                4.0,
+                Box::new(move |t: f64| {
+                    let u = half_pi * t.sinh();
+                    (u.sinh(), half_pi * t.cosh() * u.cosh())
+                }),
+            ),
+        };
+
+        let k_max = (t_max / self.step).ceil() as i64;
+        (-k_max..=k_max)
+            .map(|k| transform(k as f64 * self.step))
+            .filter(|&(x, w)| x > left && x < right && w.is_finite() && w > 0.0)
+            .map(|(x, w)| (x, w * self.step))
+            .collect()
+    }
+}
+
+impl Integrator for TanhSinhIntegrator {
+    fn integrate(&self, f: &dyn Function, left: f64, right: f64) -> f64 {
+        integrate_rule(self, f, left, right)
+    }
+}
+
+/// Maps infinite ranges onto finite ones and integrates there with `inner`:
+/// `x = t / (1 - t²)` on `(-1, 1)` for the whole line and `x = left + t / (1 - t)` on `[0, 1)`
+/// for a half-line. Finite ranges are passed to `inner` unchanged. The transformed integrand is
+/// taken as zero at the singular ends, so `inner` should be a rule that never evaluates them,
+/// such as Gauss–Legendre. Accepts infinite bounds.
+pub struct AlgebraicMapIntegrator {
+    inner: Box<dyn Integrator>,
+}
+
+impl AlgebraicMapIntegrator {
+    pub fn new(inner: Box<dyn Integrator>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Integrator for AlgebraicMapIntegrator {
+    fn integrate(&self, f: &dyn Function, left: f64, right: f64) -> f64 {
+        match (left.is_finite(), right.is_finite()) {
+            (true, true) => self.inner.integrate(f, left, right),
+            (true, false) => self.inner.integrate(
+                &|t: f64| {
+                    if t >= 1.0 {
+                        return 0.0;
+                    }
+                    f.get(left + t / (1.0 - t)) / (1.0 - t).powi(2)
+                },
+                0.0,
+                1.0,
+            ),
+            (false, true) => self.inner.integrate(
+                &|t: f64| {
+                    if t >= 1.0 {
+                        return 0.0;
+                    }
+                    f.get(right - t / (1.0 - t)) / (1.0 - t).powi(2)
+                },
+                0.0,
+                1.0,
+            ),
+            (false, false) => self.inner.integrate(
+                &|t: f64| {
+                    let d = 1.0 - t * t;
+                    if d <= 0.0 {
+                        return 0.0;
+                    }
+                    f.get(t / d) * (1.0 + t * t) / (d * d)
+                },
+                -1.0,
+                1.0,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `∫ x^k dx` over `[left, right]`.
+    fn monomial_integral(k: i32, left: f64, right: f64) -> f64 {
+        (right.powi(k + 1) - left.powi(k + 1)) / (k + 1) as f64
+    }
+
+    #[test]
+    fn gauss_legendre_is_exact_up_to_degree_2n_minus_1() {
+        for order in 1..=12 {
+            let integrator = GaussLegendreIntegrator::new(order);
+            for k in 0..2 * order as i32 {
+                let exact = monomial_integral(k, -0.5, 2.0);
+                let value = integrator.integrate(&|x: f64| x.powi(k), -0.5, 2.0);
+                assert!(
+                    (value - exact).abs() < 1e-13 * exact.abs().max(1.0),
+                    "order {order}, degree {k}: {value} != {exact}"
+                );
+            }
+            let k = 2 * order as i32;
+            let exact = monomial_integral(k, -0.5, 2.0);
+            let value = integrator.integrate(&|x: f64| x.powi(k), -0.5, 2.0);
+            assert!((value - exact).abs() > 1e-12 * exact, "order {order}");
+        }
+    }
+
+    #[test]
+    fn composite_gauss_legendre_converges_with_order_2n() {
+        let f = |x: f64| x.sin();
+        let exact = 1.0 - 2.0f64.cos();
+        let error = |subintervals| {
+            (GaussLegendreIntegrator::composite(2, subintervals).integrate(&f, 0.0, 2.0) - exact)
+                .abs()
+        };
+        // Halving the step divides the error of a 2-point rule by about 2⁴.
+        let ratio = error(8) / error(16);
+        assert!((ratio - 16.0).abs() < 0.5, "{ratio}");
+    }
+
+    #[test]
+    #[should_panic(expected = "order")]
+    fn gauss_legendre_rejects_order_zero() {
+        GaussLegendreIntegrator::new(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "subinterval")]
+    fn gauss_legendre_rejects_zero_subintervals() {
+        GaussLegendreIntegrator::composite(4, 0);
+    }
+
+    #[test]
+    fn gauss_kronrod_estimates_its_error_and_counts_evaluations() {
+        let f = |x: f64| (-2.0 * x.abs()).exp();
+        let exact = (2.0 - (-6.0f64).exp() - (-10.0f64).exp()) / 2.0;
+
+        let counted = |f: &dyn Function| {
+            let evaluations = AtomicUsize::new(0);
+            let result = GaussKronrodIntegrator::new(1e-12, 0.0, 1000).integrate_with_estimate(
+                &|x: f64| {
+                    evaluations.fetch_add(1, AtomicOrdering::Relaxed);
+                    f.get(x)
+                },
+                -3.0,
+                5.0,
+            );
+            (result, evaluations.into_inner())
+        };
+        let (result, evaluations) = counted(&f);
+
+        let error_estimate = result.error_estimate.unwrap();
+        assert!((result.value - exact).abs() < 1e-12);
+        assert!(error_estimate <= 1e-12);
+        assert!((result.value - exact).abs() <= error_estimate);
+        // The kink at 0 forces subdivision, and every split costs two 15-point pieces.
+        assert!(result.evaluations > 15);
+        assert_eq!(result.evaluations % 30, 15);
+        assert_eq!(result.evaluations, evaluations);
+
+        // The 15-point pair is exact on a polynomial of degree 7, so no split is needed.
+        let (result, evaluations) = counted(&|x: f64| (x / 5.0).powi(7) - x / 5.0);
+        assert_eq!((result.evaluations, evaluations), (15, 15));
+    }
+
+    #[test]
+    fn fixed_rules_report_evaluations_without_an_estimate() {
+        let result = GaussLegendreIntegrator::composite(4, 5).integrate_with_estimate(
+            &|x: f64| x.cos(),
+            0.0,
+            1.0,
+        );
+        assert_eq!(result.evaluations, 20);
+        assert_eq!(result.error_estimate, None);
+        assert!((result.value - 1.0f64.sin()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn simpson_is_exact_on_cubics_and_converges_with_order_4() {
+        let cubic = SimpsonIntegrator::new(3).integrate(&|x: f64| x * x * x - 2.0 * x, 0.0, 2.0);
+        assert!(cubic.abs() < 1e-14);
+
+        let exact = 1.0f64.exp() - 1.0;
+        let error = |nodes| {
+            (SimpsonIntegrator::new(nodes).integrate(&|x: f64| x.exp(), 0.0, 1.0) - exact).abs()
+        };
+        let ratio = error(17) / error(33);
+        assert!((ratio - 16.0).abs() < 0.1, "{ratio}");
+    }
+
+    #[test]
+    fn romberg_tableau_shows_orders_2_and_4() {
+        let romberg = RombergIntegrator::new(7);
+        let tableau = romberg.tableau(&|x: f64| x.exp(), 0.0, 1.0);
+
+        let orders = RombergIntegrator::observed_orders(&tableau);
+        assert_eq!(orders.len(), 5);
+        for order in &orders {
+            assert!((order - 2.0).abs() < 0.05, "{order}");
+        }
+        assert!((orders[4] - 2.0).abs() < 1e-3, "{}", orders[4]);
+        // One extrapolation removes the `h²` term.
+        let second: Vec<f64> = tableau[1..].iter().map(|row| row[1]).collect();
+        let order = ((second[3] - second[2]) / (second[4] - second[3])).log2();
+        assert!((order - 4.0).abs() < 0.05, "{order}");
+
+        let exact = 1.0f64.exp() - 1.0;
+        assert!((romberg.integrate(&|x: f64| x.exp(), 0.0, 1.0) - exact).abs() < 1e-14);
+    }
+
+    #[test]
+    fn integrates_over_infinite_ranges() {
+        use std::f64::consts::{FRAC_PI_2, PI};
+
+        let f = |x: f64| 1.0 / (1.0 + x * x);
+        let integrators: [&dyn Integrator; 2] = [
+            &TanhSinhIntegrator::new(0.05),
+            &AlgebraicMapIntegrator::new(Box::new(GaussLegendreIntegrator::new(40))),
+        ];
+        for integrator in integrators {
+            for (left, right, exact) in [
+                (0.0, f64::INFINITY, FRAC_PI_2),
+                (f64::NEG_INFINITY, 0.0, FRAC_PI_2),
+                (f64::NEG_INFINITY, f64::INFINITY, PI),
+                (-1.0, 1.0, FRAC_PI_2),
+            ] {
+                let value = integrator.integrate(&f, left, right);
+                assert!((value - exact).abs() < 1e-12, "[{left}, {right}]: {value}");
+            }
+        }
+    }
+
+    #[test]
+    fn tanh_sinh_handles_end_point_singularities() {
+        let value = TanhSinhIntegrator::new(0.05).integrate(&|x: f64| 1.0 / x.sqrt(), 0.0, 1.0);
+        assert!((value - 2.0).abs() < 1e-10, "{value}");
+    }
+
+    #[test]
+    fn clenshaw_curtis_is_exact_for_polynomials() {
+        // Order `n` integrates polynomials of degree `n` exactly, and `n + 1` for even `n`.
+        for (order, degree) in [(1, 1), (2, 3), (5, 5), (8, 9)] {
+            let rule = ClenshawCurtisIntegrator::new(order);
+            let integral = rule.integrate(&|x: f64| x.powi(degree), 0.0, 2.0);
+            let exact = 2f64.powi(degree + 1) / (degree + 1) as f64;
+            assert!(
+                (integral - exact).abs() < 1e-12,
+                "order {order}: {integral} != {exact}"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "order")]
+    fn clenshaw_curtis_rejects_order_zero() {
+        ClenshawCurtisIntegrator::new(0);
+    }
+}
@@ -0,0 +1,500 @@
+use rayon::prelude::*;
+
+use crate::functions::Function;
+
+/// What a grid function returns outside the interval it was built on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Extrapolation {
+    /// The value at the nearest end.
+    Clamp,
+    /// Mirrored about the left end, so a grid on `[0, width]` gives an even function, and
+    /// clamped beyond the right end.
+    EvenReflection,
+    /// Mirrored about the left end like `EvenReflection`, and beyond the right end relaxes
+    /// exponentially to 1 at the rate the function has there, like a solution `c` does.
+    DecayToOne,
+    /// NaN, so that evaluating outside the grid cannot go unnoticed.
+    Error,
+}
+
+impl Extrapolation {
+    /// Whether a grid on `[0, width]` becomes an even function.
+    pub fn is_even(self) -> bool {
+        matches!(
+            self,
+            Extrapolation::EvenReflection | Extrapolation::DecayToOne
+        )
+    }
+
+    fn apply(self, x: f64, left: f64, right: f64, inside: impl Fn(f64) -> f64) -> f64 {
+        let x = match self {
+            Extrapolation::EvenReflection | Extrapolation::DecayToOne if x < left => 2.0 * left - x,
+            _ => x,
+        };
+
+        if (left..=right).contains(&x) {
+            return inside(x);
+        }
+        match self {
+            Extrapolation::Error => f64::NAN,
+            Extrapolation::Clamp | Extrapolation::EvenReflection => inside(x.clamp(left, right)),
+            Extrapolation::DecayToOne if x < left => inside(left),
+            Extrapolation::DecayToOne => {
+                let delta = (right - left) * 1e-3;
+                let (end, before) = (inside(right), inside(right - delta));
+                let rate = (before - end) / delta / (end - 1.0);
+                if rate > 0.0 && rate.is_finite() {
+                    1.0 + (end - 1.0) * (-rate * (x - right)).exp()
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+/// Linear interpolation of values on `pts.len()` equally spaced points of `[left, right]`,
+/// at least two.
+pub struct PointFunction {
+    pts: Vec<f64>,
+    left: f64,
+    right: f64,
+    extrapolation: Extrapolation,
+}
+
+impl PointFunction {
+    pub fn new(pts: Vec<f64>, left: f64, right: f64, extrapolation: Extrapolation) -> Self {
+        assert!(pts.len() >= 2, "interpolation needs at least two nodes");
+        Self {
+            pts,
+            left,
+            right,
+            extrapolation,
+        }
+    }
+}
+
+impl Function for PointFunction {
+    fn get(&self, x: f64) -> f64 {
+        self.extrapolation.apply(x, self.left, self.right, |x| {
+            let step = (self.right - self.left) / (self.pts.len() - 1) as f64;
+            let position = (x - self.left) / step;
+            let i = (position.floor() as usize).min(self.pts.len() - 2);
+            let t = position - i as f64;
+
+            self.pts[i] * (1.0 - t) + self.pts[i + 1] * t
+        })
+    }
+}
+
+/// Piecewise cubic through `values` at increasing `nodes` with the given slopes there.
+struct Hermite {
+    nodes: Vec<f64>,
+    values: Vec<f64>,
+    slopes: Vec<f64>,
+    extrapolation: Extrapolation,
+}
+
+impl Hermite {
+    fn inside(&self, x: f64) -> f64 {
+        let i = self
+            .nodes
+            .partition_point(|&node| node <= x)
+            .clamp(1, self.nodes.len() - 1)
+            - 1;
+        let h = self.nodes[i + 1] - self.nodes[i];
+        let t = (x - self.nodes[i]) / h;
+        let (t2, t3) = (t * t, t * t * t);
+
+        self.values[i] * (2.0 * t3 - 3.0 * t2 + 1.0)
+            + self.slopes[i] * h * (t3 - 2.0 * t2 + t)
+            + self.values[i + 1] * (-2.0 * t3 + 3.0 * t2)
+            + self.slopes[i + 1] * h * (t3 - t2)
+    }
+
+    fn get(&self, x: f64) -> f64 {
+        let (left, right) = (self.nodes[0], self.nodes[self.nodes.len() - 1]);
+        self.extrapolation.apply(x, left, right, |x| self.inside(x))
+    }
+}
+
+fn check_nodes(nodes: &[f64], values: &[f64]) {
+    assert!(nodes.len() >= 2, "interpolation needs at least two nodes");
+    assert_eq!(nodes.len(), values.len(), "one value per node");
+}
+
+/// Slopes of the secants between successive nodes.
+fn secants(nodes: &[f64], values: &[f64]) -> Vec<f64> {
+    nodes
+        .windows(2)
+        .zip(values.windows(2))
+        .map(|(x, y)| (y[1] - y[0]) / (x[1] - x[0]))
+        .collect()
+}
+
+/// Twice continuously differentiable cubic spline through values at increasing nodes.
+pub struct CubicSpline {
+    hermite: Hermite,
+}
+
+impl CubicSpline {
+    /// Zero second derivative at both ends.
+    pub fn natural(nodes: Vec<f64>, values: Vec<f64>, extrapolation: Extrapolation) -> Self {
+        Self::with_ends(nodes, values, None, extrapolation)
+    }
+
+    /// The given first derivatives at the ends.
+    pub fn clamped(
+        nodes: Vec<f64>,
+        values: Vec<f64>,
+        left_slope: f64,
+        right_slope: f64,
+        extrapolation: Extrapolation,
+    ) -> Self {
+        Self::with_ends(
+            nodes,
+            values,
+            Some((left_slope, right_slope)),
+            extrapolation,
+        )
+    }
+
+    /// Solves the tridiagonal system for the slopes by the Thomas algorithm.
+    fn with_ends(
+        nodes: Vec<f64>,
+        values: Vec<f64>,
+        end_slopes: Option<(f64, f64)>,
+        extrapolation: Extrapolation,
+    ) -> Self {
+        check_nodes(&nodes, &values);
+        let n = nodes.len();
+        let h: Vec<f64> = nodes.windows(2).map(|x| x[1] - x[0]).collect();
+        let delta = secants(&nodes, &values);
+
+        // Row i is `lower[i] s[i - 1] + diagonal[i] s[i] + upper[i] s[i + 1] = right[i]`.
+        let mut lower: Vec<f64> = (0..n).map(|_| 0.0).collect();
+        let mut diagonal = lower.clone();
+        let mut upper = lower.clone();
+        let mut right = lower.clone();
+        for i in 1..n - 1 {
+            lower[i] = h[i];
+            diagonal[i] = 2.0 * (h[i - 1] + h[i]);
+            upper[i] = h[i - 1];
+            right[i] = 3.0 * (h[i] * delta[i - 1] + h[i - 1] * delta[i]);
+        }
+        match end_slopes {
+            Some((left_slope, right_slope)) => {
+                diagonal[0] = 1.0;
+                right[0] = left_slope;
+                diagonal[n - 1] = 1.0;
+                right[n - 1] = right_slope;
+            }
+            None => {
+                diagonal[0] = 2.0;
+                upper[0] = 1.0;
+                right[0] = 3.0 * delta[0];
+                lower[n - 1] = 1.0;
+                diagonal[n - 1] = 2.0;
+                right[n - 1] = 3.0 * delta[n - 2];
+            }
+        }
+
+        for i in 1..n {
+            let factor = lower[i] / diagonal[i - 1];
+            diagonal[i] -= factor * upper[i - 1];
+            right[i] -= factor * right[i - 1];
+        }
+        let mut slopes = right;
+        slopes[n - 1] /= diagonal[n - 1];
+        for i in (0..n - 1).rev() {
+            slopes[i] = (slopes[i] - upper[i] * slopes[i + 1]) / diagonal[i];
+        }
+
+        Self {
+            hermite: Hermite {
+                nodes,
+                values,
+                slopes,
+                extrapolation,
+            },
+        }
+    }
+}
+
+impl Function for CubicSpline {
+    fn get(&self, x: f64) -> f64 {
+        self.hermite.get(x)
+    }
+}
+
+/// Monotone piecewise cubic Hermite interpolation of Fritsch and Carlson: no overshoot
+/// between the values, at the cost of a discontinuous second derivative.
+pub struct Pchip {
+    hermite: Hermite,
+}
+
+impl Pchip {
+    pub fn new(nodes: Vec<f64>, values: Vec<f64>, extrapolation: Extrapolation) -> Self {
+        check_nodes(&nodes, &values);
+        let n = nodes.len();
+        let h: Vec<f64> = nodes.windows(2).map(|x| x[1] - x[0]).collect();
+        let delta = secants(&nodes, &values);
+
+        let mut slopes: Vec<f64> = (0..n).map(|_| 0.0).collect();
+        if n == 2 {
+            slopes = vec![delta[0], delta[0]];
+        } else {
+            for i in 1..n - 1 {
+                if delta[i - 1] * delta[i] > 0.0 {
+                    let (w1, w2) = (2.0 * h[i] + h[i - 1], h[i] + 2.0 * h[i - 1]);
+                    slopes[i] = (w1 + w2) / (w1 / delta[i - 1] + w2 / delta[i]);
+                }
+            }
+            // One-sided three point estimates, limited to keep the ends monotone.
+            let end = |h0: f64, h1: f64, d0: f64, d1: f64| {
+                let slope = ((2.0 * h0 + h1) * d0 - h0 * d1) / (h0 + h1);
+                if slope * d0 <= 0.0 {
+                    0.0
+                } else if d0 * d1 < 0.0 && slope.abs() > 3.0 * d0.abs() {
+                    3.0 * d0
+                } else {
+                    slope
+                }
+            };
+            slopes[0] = end(h[0], h[1], delta[0], delta[1]);
+            slopes[n - 1] = end(h[n - 2], h[n - 3], delta[n - 2], delta[n - 3]);
+        }
+
+        Self {
+            hermite: Hermite {
+                nodes,
+                values,
+                slopes,
+                extrapolation,
+            },
+        }
+    }
+}
+
+impl Function for Pchip {
+    fn get(&self, x: f64) -> f64 {
+        self.hermite.get(x)
+    }
+}
+
+/// `count` Chebyshev points of the second kind `(left + right) / 2 - (right - left) / 2 cos(jπ / (count - 1))`,
+/// increasing from `left` to `right`.
+pub fn chebyshev_points(left: f64, right: f64, count: usize) -> Vec<f64> {
+    (0..count)
+        .map(|j| {
+            let angle = j as f64 * std::f64::consts::PI / (count - 1) as f64;
+            (left + right) / 2.0 - (right - left) / 2.0 * angle.cos()
+        })
+        .collect()
+}
+
+/// Polynomial interpolation of values at `chebyshev_points(left, right, values.len())`,
+/// evaluated by the barycentric formula, which is stable for these points.
+pub struct ChebyshevInterpolant {
+    nodes: Vec<f64>,
+    values: Vec<f64>,
+    left: f64,
+    right: f64,
+    extrapolation: Extrapolation,
+}
+
+impl ChebyshevInterpolant {
+    pub fn new(values: Vec<f64>, left: f64, right: f64, extrapolation: Extrapolation) -> Self {
+        assert!(values.len() >= 2, "interpolation needs at least two nodes");
+        Self {
+            nodes: chebyshev_points(left, right, values.len()),
+            values,
+            left,
+            right,
+            extrapolation,
+        }
+    }
+
+    fn inside(&self, x: f64) -> f64 {
+        let n = self.nodes.len();
+        let (mut numerator, mut denominator) = (0.0, 0.0);
+        for (j, (node, value)) in self.nodes.iter().zip(&self.values).enumerate() {
+            if x == *node {
+                return *value;
+            }
+            let sign = if j % 2 == 0 { 1.0 } else { -1.0 };
+            let weight = if j == 0 || j == n - 1 {
+                sign / 2.0
+            } else {
+                sign
+            } / (x - node);
+            numerator += weight * value;
+            denominator += weight;
+        }
+        numerator / denominator
+    }
+}
+
+impl Function for ChebyshevInterpolant {
+    fn get(&self, x: f64) -> f64 {
+        self.extrapolation
+            .apply(x, self.left, self.right, |x| self.inside(x))
+    }
+}
+
+/// Which grid function is built from sampled values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    NaturalSpline,
+    ClampedSpline { left_slope: f64, right_slope: f64 },
+    Pchip,
+    Chebyshev,
+}
+
+impl Interpolation {
+    /// Where the interpolant wants its values: Chebyshev points for `Chebyshev`, equally
+    /// spaced points otherwise.
+    pub fn points(self, left: f64, right: f64, count: usize) -> Vec<f64> {
+        match self {
+            Interpolation::Chebyshev => chebyshev_points(left, right, count),
+            _ => (0..count)
+                .map(|i| left + (right - left) * i as f64 / (count - 1) as f64)
+                .collect(),
+        }
+    }
+
+    /// Interpolates `values` given at `self.points(left, right, values.len())`.
+    pub fn from_values(
+        self,
+        values: Vec<f64>,
+        left: f64,
+        right: f64,
+        extrapolation: Extrapolation,
+    ) -> Box<dyn Function> {
+        let nodes = || self.points(left, right, values.len());
+        match self {
+            Interpolation::Linear => {
+                Box::new(PointFunction::new(values, left, right, extrapolation))
+            }
+            Interpolation::NaturalSpline => {
+                Box::new(CubicSpline::natural(nodes(), values, extrapolation))
+            }
+            Interpolation::ClampedSpline {
+                left_slope,
+                right_slope,
+            } => Box::new(CubicSpline::clamped(
+                nodes(),
+                values,
+                left_slope,
+                right_slope,
+                extrapolation,
+            )),
+            Interpolation::Pchip => Box::new(Pchip::new(nodes(), values, extrapolation)),
+            Interpolation::Chebyshev => Box::new(ChebyshevInterpolant::new(
+                values,
+                left,
+                right,
+                extrapolation,
+            )),
+        }
+    }
+
+    /// Samples `f` at `count` of its points and interpolates it, `count` is at least 2.
+    pub fn tabulate(
+        self,
+        f: &dyn Function,
+        left: f64,
+        right: f64,
+        count: usize,
+        extrapolation: Extrapolation,
+    ) -> Box<dyn Function> {
+        assert!(count >= 2, "interpolation needs at least two nodes");
+        let values = self
+            .points(left, right, count)
+            .par_iter()
+            .map(|&x| f.get(x))
+            .collect();
+        self.from_values(values, left, right, extrapolation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uniform(count: usize) -> Vec<f64> {
+        Interpolation::Linear.points(0.0, 2.0, count)
+    }
+
+    fn assert_reproduces(f: &dyn Function, exact: impl Fn(f64) -> f64) {
+        for i in 0..=40 {
+            let x = 2.0 * i as f64 / 40.0;
+            assert!(
+                (f.get(x) - exact(x)).abs() < 1e-12,
+                "{} != {}",
+                f.get(x),
+                exact(x)
+            );
+        }
+    }
+
+    #[test]
+    fn exact_on_polynomials_of_their_degree() {
+        let line = |x: f64| 3.0 * x - 1.0;
+        let cubic = |x: f64| x * x * x - 2.0 * x * x + 0.5;
+        let values = |f: &dyn Fn(f64) -> f64, nodes: &[f64]| -> Vec<f64> {
+            nodes.iter().map(|&x| f(x)).collect()
+        };
+        let nodes = uniform(7);
+
+        for interpolation in [
+            Interpolation::Linear,
+            Interpolation::NaturalSpline,
+            Interpolation::Pchip,
+        ] {
+            let f =
+                interpolation.from_values(values(&line, &nodes), 0.0, 2.0, Extrapolation::Clamp);
+            assert_reproduces(f.as_ref(), line);
+        }
+
+        let clamped = Interpolation::ClampedSpline {
+            left_slope: 0.0,
+            right_slope: 4.0,
+        };
+        let f = clamped.from_values(values(&cubic, &nodes), 0.0, 2.0, Extrapolation::Clamp);
+        assert_reproduces(f.as_ref(), cubic);
+
+        let f = Interpolation::Chebyshev.tabulate(&cubic, 0.0, 2.0, 4, Extrapolation::Clamp);
+        assert_reproduces(f.as_ref(), cubic);
+    }
+
+    #[test]
+    fn pchip_stays_monotone() {
+        let nodes = uniform(8);
+        let values = vec![0.0, 0.0, 0.1, 0.2, 1.8, 1.9, 2.0, 2.0];
+        let f = Pchip::new(nodes, values, Extrapolation::Clamp);
+
+        let samples: Vec<f64> = (0..=400).map(|i| f.get(2.0 * i as f64 / 400.0)).collect();
+        assert!(samples.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert!(samples.iter().all(|&v| (0.0..=2.0).contains(&v)));
+    }
+
+    #[test]
+    #[should_panic(expected = "at least two nodes")]
+    fn point_function_needs_two_nodes() {
+        PointFunction::new(vec![1.0], 0.0, 1.0, Extrapolation::Clamp);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least two nodes")]
+    fn spline_needs_two_nodes() {
+        CubicSpline::natural(vec![0.0], vec![1.0], Extrapolation::Clamp);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least two nodes")]
+    fn tabulate_needs_two_nodes() {
+        Interpolation::Chebyshev.tabulate(&|x: f64| x, 0.0, 1.0, 1, Extrapolation::Clamp);
+    }
+}
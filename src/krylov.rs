@@ -0,0 +1,415 @@
+use rayon::prelude::*;
+
+use crate::{
+    linear_equation::{Factorization, LinearEquationSolver},
+    method::SolveError,
+};
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn norm(a: &[f64]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+pub struct KrylovSolution {
+    pub x: Vec<f64>,
+    /// `|b - A x| / |b|` after every iteration.
+    pub residual_history: Vec<f64>,
+    /// Whether the tolerance was met, otherwise `x` is the last iterate.
+    pub converged: bool,
+}
+
+/// Solves `operator(x) = b` using only products with the operator, so the matrix never
+/// has to be stored.
+pub trait IterativeSolver: Sync {
+    /// Iterates until the tolerance is met, the iteration limit is reached or the method
+    /// breaks down, and returns the last iterate in any case.
+    fn iterate(
+        &self,
+        operator: &dyn Fn(&[f64]) -> Vec<f64>,
+        b: &[f64],
+    ) -> Result<KrylovSolution, SolveError>;
+
+    /// Like `iterate`, but missing the tolerance is a `SolveError::NotConverged`.
+    fn solve_operator(
+        &self,
+        operator: &dyn Fn(&[f64]) -> Vec<f64>,
+        b: &[f64],
+    ) -> Result<KrylovSolution, SolveError> {
+        let solution = self.iterate(operator, b)?;
+        if solution.converged {
+            Ok(solution)
+        } else {
+            Err(SolveError::NotConverged {
+                iterations: solution.residual_history.len(),
+                residual: solution.residual_history.last().copied().unwrap_or(1.0),
+            })
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GmresSolver {
+    tolerance: f64,
+    restart: usize,
+    max_iterations: usize,
+}
+
+impl GmresSolver {
+    /// Panics if `restart` is 0, a cycle has to take at least one step.
+    pub fn new(tolerance: f64, restart: usize, max_iterations: usize) -> Self {
+        assert!(restart >= 1, "GMRES restart length must be at least 1");
+        Self {
+            tolerance,
+            restart,
+            max_iterations,
+        }
+    }
+}
+
+impl IterativeSolver for GmresSolver {
+    fn iterate(
+        &self,
+        operator: &dyn Fn(&[f64]) -> Vec<f64>,
+        b: &[f64],
+    ) -> Result<KrylovSolution, SolveError> {
+        let n = b.len();
+        let mut x: Vec<f64> = (0..n).map(|_| 0.0).collect();
+        let mut residual_history = Vec::new();
+
+        let b_norm = norm(b);
+        if b_norm == 0.0 {
+            return Ok(KrylovSolution {
+                x,
+                residual_history,
+                converged: true,
+            });
+        }
+
+        let mut r = b.to_owned();
+        let mut beta = b_norm;
+
+        loop {
+            let mut basis: Vec<Vec<f64>> = vec![r.iter().map(|v| v / beta).collect()];
+            let mut hessenberg: Vec<Vec<f64>> = Vec::new();
+            let mut cs: Vec<f64> = Vec::new();
+            let mut sn: Vec<f64> = Vec::new();
+            let mut g = vec![beta];
+            let mut breakdown = false;
+
+            for k in 0..self.restart {
+                let mut w = operator(&basis[k]);
+
+                let mut column: Vec<f64> = (0..k + 2).map(|_| 0.0).collect();
+                for (i, v) in basis.iter().enumerate() {
+                    column[i] = dot(&w, v);
+                    for (w, v) in w.iter_mut().zip(v) {
+                        *w -= column[i] * v;
+                    }
+                }
+                column[k + 1] = norm(&w);
+                let next_norm = column[k + 1];
+
+                for i in 0..k {
+                    let temp = cs[i] * column[i] + sn[i] * column[i + 1];
+                    column[i + 1] = -sn[i] * column[i] + cs[i] * column[i + 1];
+                    column[i] = temp;
+                }
+                let denominator = column[k].hypot(column[k + 1]);
+                if denominator == 0.0 || !denominator.is_finite() {
+                    // The new Hessenberg column vanished, the Krylov space cannot grow.
+                    breakdown = true;
+                    break;
+                }
+                cs.push(column[k] / denominator);
+                sn.push(column[k + 1] / denominator);
+                column[k] = denominator;
+                column[k + 1] = 0.0;
+                g.push(-sn[k] * g[k]);
+                g[k] *= cs[k];
+                hessenberg.push(column);
+                residual_history.push(g[k + 1].abs() / b_norm);
+
+                if g[k + 1].abs() <= self.tolerance * b_norm
+                    || residual_history.len() >= self.max_iterations
+                    || next_norm == 0.0
+                {
+                    break;
+                }
+                basis.push(w.iter().map(|v| v / next_norm).collect());
+            }
+
+            let m = hessenberg.len();
+            let mut y: Vec<f64> = (0..m).map(|_| 0.0).collect();
+            for i in (0..m).rev() {
+                let mut yi = g[i];
+                for j in i + 1..m {
+                    yi -= hessenberg[j][i] * y[j];
+                }
+                y[i] = yi / hessenberg[i][i];
+            }
+            for (v, y) in basis.iter().zip(&y) {
+                for (x, v) in x.iter_mut().zip(v) {
+                    *x += y * v;
+                }
+            }
+
+            r = operator(&x).iter().zip(b).map(|(ax, b)| b - ax).collect();
+            beta = norm(&r);
+
+            let converged = beta <= self.tolerance * b_norm;
+            if converged || breakdown || residual_history.len() >= self.max_iterations {
+                return Ok(KrylovSolution {
+                    x,
+                    residual_history,
+                    converged,
+                });
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BiCgStabSolver {
+    tolerance: f64,
+    max_iterations: usize,
+}
+
+impl BiCgStabSolver {
+    pub fn new(tolerance: f64, max_iterations: usize) -> Self {
+        Self {
+            tolerance,
+            max_iterations,
+        }
+    }
+}
+
+impl IterativeSolver for BiCgStabSolver {
+    fn iterate(
+        &self,
+        operator: &dyn Fn(&[f64]) -> Vec<f64>,
+        b: &[f64],
+    ) -> Result<KrylovSolution, SolveError> {
+        let n = b.len();
+        let mut x: Vec<f64> = (0..n).map(|_| 0.0).collect();
+        let mut residual_history = Vec::new();
+
+        let b_norm = norm(b);
+        if b_norm == 0.0 {
+            return Ok(KrylovSolution {
+                x,
+                residual_history,
+                converged: true,
+            });
+        }
+
+        let mut r = b.to_owned();
+        let shadow = r.clone();
+        let mut p: Vec<f64> = (0..n).map(|_| 0.0).collect();
+        let mut v = p.clone();
+        let (mut rho, mut alpha, mut omega) = (1.0, 1.0, 1.0);
+
+        while residual_history.len() < self.max_iterations {
+            let rho_next = dot(&shadow, &r);
+            if rho_next == 0.0 || omega == 0.0 {
+                break;
+            }
+
+            let beta = (rho_next / rho) * (alpha / omega);
+            for i in 0..n {
+                p[i] = r[i] + beta * (p[i] - omega * v[i]);
+            }
+            v = operator(&p);
+            let shadow_v = dot(&shadow, &v);
+            if shadow_v == 0.0 || !shadow_v.is_finite() {
+                break;
+            }
+            alpha = rho_next / shadow_v;
+
+            let s: Vec<f64> = r.iter().zip(&v).map(|(r, v)| r - alpha * v).collect();
+            if norm(&s) <= self.tolerance * b_norm {
+                for (x, p) in x.iter_mut().zip(&p) {
+                    *x += alpha * p;
+                }
+                residual_history.push(norm(&s) / b_norm);
+                return Ok(KrylovSolution {
+                    x,
+                    residual_history,
+                    converged: true,
+                });
+            }
+
+            let t = operator(&s);
+            let t_norm = dot(&t, &t);
+            if t_norm == 0.0 || !t_norm.is_finite() {
+                break;
+            }
+            omega = dot(&t, &s) / t_norm;
+            for i in 0..n {
+                x[i] += alpha * p[i] + omega * s[i];
+                r[i] = s[i] - omega * t[i];
+            }
+            rho = rho_next;
+
+            let residual = norm(&r) / b_norm;
+            residual_history.push(residual);
+            if residual <= self.tolerance {
+                return Ok(KrylovSolution {
+                    x,
+                    residual_history,
+                    converged: true,
+                });
+            }
+        }
+
+        Ok(KrylovSolution {
+            x,
+            residual_history,
+            converged: false,
+        })
+    }
+}
+
+/// Keeps the dense matrix so an iterative solver can stand in for a factorization.
+struct DenseOperator<S> {
+    mat: Vec<f64>,
+    width: usize,
+    solver: S,
+}
+
+impl<S: IterativeSolver> Factorization for DenseOperator<S> {
+    fn solve(&self, b: &[f64]) -> Result<Vec<f64>, SolveError> {
+        if b.len() != self.width {
+            return Err(SolveError::DimensionMismatch {
+                expected: self.width,
+                found: b.len(),
+            });
+        }
+
+        let operator = |x: &[f64]| {
+            self.mat
+                .par_chunks(self.width)
+                .map(|row| dot(row, x))
+                .collect()
+        };
+        Ok(self.solver.solve_operator(&operator, b)?.x)
+    }
+}
+
+fn dense_operator<S: IterativeSolver + Copy + 'static>(
+    solver: S,
+    mat: &[f64],
+    width: usize,
+) -> Result<Box<dyn Factorization>, SolveError> {
+    if width * width != mat.len() {
+        return Err(SolveError::DimensionMismatch {
+            expected: width * width,
+            found: mat.len(),
+        });
+    }
+
+    Ok(Box::new(DenseOperator {
+        mat: mat.to_owned(),
+        width,
+        solver,
+    }))
+}
+
+impl LinearEquationSolver for GmresSolver {
+    fn factorize(&self, mat: &[f64], width: usize) -> Result<Box<dyn Factorization>, SolveError> {
+        dense_operator(*self, mat, width)
+    }
+}
+
+impl LinearEquationSolver for BiCgStabSolver {
+    fn factorize(&self, mat: &[f64], width: usize) -> Result<Box<dyn Factorization>, SolveError> {
+        dense_operator(*self, mat, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linear_equation::LUSolver;
+
+    /// `x ↦ A x` for a row-major `width × width` matrix.
+    fn matrix(mat: &[f64]) -> impl Fn(&[f64]) -> Vec<f64> + '_ {
+        let width = (mat.len() as f64).sqrt() as usize;
+        move |x: &[f64]| mat.chunks(width).map(|row| dot(row, x)).collect()
+    }
+
+    #[test]
+    fn solves_small_system() {
+        let mat = [4.0, 1.0, 0.0, 1.0, 3.0, 1.0, 0.0, 1.0, 2.0];
+        let b = [1.0, 2.0, 3.0];
+        let solvers: [&dyn IterativeSolver; 3] = [
+            &GmresSolver::new(1e-14, 3, 100),
+            &GmresSolver::new(1e-14, 1, 100),
+            &BiCgStabSolver::new(1e-14, 100),
+        ];
+        for solver in solvers {
+            let solution = solver.solve_operator(&matrix(&mat), &b).unwrap();
+            let residual = matrix(&mat)(&solution.x);
+            for (r, b) in residual.iter().zip(&b) {
+                assert!((r - b).abs() < 1e-12);
+            }
+            assert!(solution.residual_history.last().unwrap() <= &1e-14);
+        }
+    }
+
+    #[test]
+    fn full_gmres_residuals_do_not_grow() {
+        let width = 20;
+        let mat: Vec<f64> = (0..width * width)
+            .map(|k| {
+                let (i, j) = (k / width, k % width);
+                if i == j {
+                    3.0
+                } else {
+                    1.0 / (1.0 + (i as f64 - j as f64).powi(2))
+                }
+            })
+            .collect();
+        let b: Vec<f64> = (0..width).map(|i| (i as f64).cos()).collect();
+
+        let solution = GmresSolver::new(1e-12, width, 100)
+            .solve_operator(&matrix(&mat), &b)
+            .unwrap();
+        for pair in solution.residual_history.windows(2) {
+            assert!(pair[1] <= pair[0] * (1.0 + 1e-12));
+        }
+
+        let dense = LUSolver.solve(&mat, width, &b).unwrap();
+        let through_factorization = GmresSolver::new(1e-12, width, 100)
+            .factorize(&mat, width)
+            .unwrap()
+            .solve(&b)
+            .unwrap();
+        for (a, b) in through_factorization.iter().zip(&dense) {
+            assert!((a - b).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "restart")]
+    fn gmres_rejects_zero_restart() {
+        GmresSolver::new(1e-10, 0, 100);
+    }
+
+    #[test]
+    fn gmres_stops_on_breakdown() {
+        let mat = [0.0; 4];
+        let result = GmresSolver::new(1e-10, 10, 100).solve_operator(&matrix(&mat), &[1.0, 0.0]);
+        assert!(matches!(result, Err(SolveError::NotConverged { .. })));
+    }
+
+    #[test]
+    fn bicgstab_stops_on_breakdown() {
+        // The first search direction is orthogonal to the shadow residual.
+        let mat = [0.0, 1.0, 1.0, 0.0];
+        let result = BiCgStabSolver::new(1e-10, 100).solve_operator(&matrix(&mat), &[1.0, 0.0]);
+        assert!(matches!(result, Err(SolveError::NotConverged { .. })));
+    }
+}
@@ -0,0 +1,22 @@
+pub mod basis;
+pub mod collocation;
+pub mod convolution;
+pub mod expression;
+pub mod fft;
+pub mod functions;
+pub mod galerkin;
+pub mod integrals;
+pub mod interpolation;
+pub mod krylov;
+pub mod linear_equation;
+pub mod method;
+pub mod neumann;
+pub mod nystrom;
+pub mod nystrom_toeplitz;
+pub mod problem;
+pub mod registry;
+pub mod residual;
+pub mod sloan;
+pub mod testing;
+pub mod toeplitz;
+pub mod two_grid;
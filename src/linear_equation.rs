@@ -1,13 +1,28 @@
+use rayon::prelude::*;
+
+use crate::method::SolveError;
+
+pub trait Factorization: Sync {
+    fn solve(&self, b: &[f64]) -> Result<Vec<f64>, SolveError>;
+}
+
 pub trait LinearEquationSolver: Sync {
-    fn solve(&self, mat: &[f64], width: usize, b: &[f64]) -> Option<Vec<f64>>;
+    fn factorize(&self, mat: &[f64], width: usize) -> Result<Box<dyn Factorization>, SolveError>;
+
+    fn solve(&self, mat: &[f64], width: usize, b: &[f64]) -> Result<Vec<f64>, SolveError> {
+        self.factorize(mat, width)?.solve(b)
+    }
 }
 
 pub struct LUSolver;
 
 impl LUSolver {
-    fn lu(&self, mat: &[f64], width: usize) -> Option<(Vec<f64>, Vec<f64>)> {
-        if width != mat.len() / width {
-            return None;
+    fn lu(&self, mat: &[f64], width: usize) -> Result<(Vec<f64>, Vec<f64>), SolveError> {
+        if width * width != mat.len() {
+            return Err(SolveError::DimensionMismatch {
+                expected: width * width,
+                found: mat.len(),
+            });
         }
 
         let mut l: Vec<f64> = (0..width * width).map(|_| 0.0).collect();
@@ -16,8 +31,8 @@ impl LUSolver {
 
         for layer in 0..width {
             let a = d[layer * width + layer];
-            if a.clone() == 0.0.into() {
-                return None;
+            if a == 0.0 {
+                return Err(SolveError::SingularMatrix { column: layer });
             }
 
             l[layer * width + layer] = 1.0;
@@ -28,27 +43,41 @@ impl LUSolver {
                 u[layer * width + i] = d[layer * width + i];
 
                 for j in layer + 1..width {
-                    d[i * width + j] =
-                        d[i * width + j] - (d[layer * width + j] * d[i * width + layer]) / a;
+                    d[i * width + j] -= (d[layer * width + j] * d[i * width + layer]) / a;
                 }
             }
         }
 
-        Some((l, u))
+        Ok((l, u))
     }
 
-    fn gauss_from_lu(&self, l: &[f64], u: &[f64], b: &[f64], width: usize) -> Option<Vec<f64>> {
+    fn gauss_from_lu(
+        &self,
+        l: &[f64],
+        u: &[f64],
+        b: &[f64],
+        width: usize,
+    ) -> Result<Vec<f64>, SolveError> {
         let l_height = l.len() / width;
         let u_height = u.len() / width;
 
-        if l_height != width || u_height != width || b.len() != width {
-            return None;
+        if l_height != width || u_height != width {
+            return Err(SolveError::DimensionMismatch {
+                expected: width,
+                found: l_height.min(u_height),
+            });
+        }
+        if b.len() != width {
+            return Err(SolveError::DimensionMismatch {
+                expected: width,
+                found: b.len(),
+            });
         }
 
         let v = self.l_gauss(l, b, width);
         let x = self.u_gauss(u, &v, width);
 
-        Some(x)
+        Ok(x)
     }
 
     fn l_gauss(&self, l: &[f64], b: &[f64], width: usize) -> Vec<f64> {
@@ -56,7 +85,7 @@ impl LUSolver {
         for i in 0..width {
             let mut xi = b[i];
             for j in 0..i {
-                xi = xi - l[i * width + j] * x[j];
+                xi -= l[i * width + j] * x[j];
             }
             x[i] = xi;
         }
@@ -70,7 +99,7 @@ impl LUSolver {
         for i in 0..width {
             let mut xi = b[width - i - 1];
             for j in 0..i {
-                xi = xi - u[(width - i - 1) * width + width - j - 1] * x[width - j - 1];
+                xi -= u[(width - i - 1) * width + width - j - 1] * x[width - j - 1];
             }
             x[width - i - 1] = xi / u[(width - i - 1) * width + width - i - 1];
         }
@@ -79,8 +108,276 @@ impl LUSolver {
 }
 
 impl LinearEquationSolver for LUSolver {
-    fn solve(&self, mat: &[f64], width: usize, b: &[f64]) -> Option<Vec<f64>> {
+    fn factorize(&self, mat: &[f64], width: usize) -> Result<Box<dyn Factorization>, SolveError> {
         let (l, u) = self.lu(mat, width)?;
-        self.gauss_from_lu(&l, &u, b, width)
+        Ok(Box::new(LUFactorization { l, u, width }))
+    }
+}
+
+struct LUFactorization {
+    l: Vec<f64>,
+    u: Vec<f64>,
+    width: usize,
+}
+
+impl Factorization for LUFactorization {
+    fn solve(&self, b: &[f64]) -> Result<Vec<f64>, SolveError> {
+        LUSolver.gauss_from_lu(&self.l, &self.u, b, self.width)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pivoting {
+    /// Swap rows so the largest entry of the current column becomes the pivot.
+    Partial,
+    /// Swap rows and columns so the largest entry of the remaining block becomes the pivot.
+    Full,
+}
+
+pub struct PivotedLUSolver {
+    pivoting: Pivoting,
+}
+
+impl PivotedLUSolver {
+    pub fn new(pivoting: Pivoting) -> Self {
+        Self { pivoting }
+    }
+
+    pub fn factorize(&self, mat: &[f64], width: usize) -> Result<PivotedLU, SolveError> {
+        if width * width != mat.len() {
+            return Err(SolveError::DimensionMismatch {
+                expected: width * width,
+                found: mat.len(),
+            });
+        }
+
+        let mut lu = mat.to_owned();
+        let mut row_permutation: Vec<usize> = (0..width).collect();
+        let mut column_permutation: Vec<usize> = (0..width).collect();
+
+        let initial_max = lu.iter().fold(0.0f64, |acc, v| acc.max(v.abs()));
+        let mut max_entry = initial_max;
+
+        for layer in 0..width {
+            // `max_by` keeps the last of equal candidates, reversing keeps the first, so a
+            // matrix that needs no swaps gets none.
+            let (pivot_row, pivot_col) = match self.pivoting {
+                Pivoting::Partial => {
+                    let row = (layer..width)
+                        .rev()
+                        .max_by(|&a, &b| {
+                            lu[a * width + layer]
+                                .abs()
+                                .total_cmp(&lu[b * width + layer].abs())
+                        })
+                        .unwrap();
+                    (row, layer)
+                }
+                Pivoting::Full => (layer..width)
+                    .flat_map(|i| (layer..width).map(move |j| (i, j)))
+                    .rev()
+                    .max_by(|&(ai, aj), &(bi, bj)| {
+                        lu[ai * width + aj]
+                            .abs()
+                            .total_cmp(&lu[bi * width + bj].abs())
+                    })
+                    .unwrap(),
+            };
+
+            if lu[pivot_row * width + pivot_col] == 0.0 {
+                return Err(SolveError::SingularMatrix { column: layer });
+            }
+
+            if pivot_row != layer {
+                for j in 0..width {
+                    lu.swap(layer * width + j, pivot_row * width + j);
+                }
+                row_permutation.swap(layer, pivot_row);
+            }
+            if pivot_col != layer {
+                for i in 0..width {
+                    lu.swap(i * width + layer, i * width + pivot_col);
+                }
+                column_permutation.swap(layer, pivot_col);
+            }
+
+            let (head, tail) = lu.split_at_mut((layer + 1) * width);
+            let pivot = &head[layer * width..];
+            let a = pivot[layer];
+
+            max_entry = tail
+                .par_chunks_mut(width)
+                .map(|row| {
+                    let factor = row[layer] / a;
+                    row[layer] = factor;
+
+                    let mut row_max = 0.0f64;
+                    for j in layer + 1..width {
+                        row[j] -= factor * pivot[j];
+                        row_max = row_max.max(row[j].abs());
+                    }
+                    row_max
+                })
+                .reduce(|| 0.0, f64::max)
+                .max(max_entry);
+        }
+
+        Ok(PivotedLU {
+            lu,
+            width,
+            row_permutation,
+            column_permutation,
+            growth_factor: if initial_max == 0.0 {
+                1.0
+            } else {
+                max_entry / initial_max
+            },
+        })
+    }
+}
+
+impl LinearEquationSolver for PivotedLUSolver {
+    fn factorize(&self, mat: &[f64], width: usize) -> Result<Box<dyn Factorization>, SolveError> {
+        Ok(Box::new(PivotedLUSolver::factorize(self, mat, width)?))
+    }
+}
+
+/// `P A Q = L U` with the unit lower triangle of `L` and the upper triangle of `U`
+/// stored in one matrix.
+pub struct PivotedLU {
+    lu: Vec<f64>,
+    width: usize,
+    row_permutation: Vec<usize>,
+    column_permutation: Vec<usize>,
+    growth_factor: f64,
+}
+
+impl PivotedLU {
+    /// Row `i` of the factorized matrix is row `row_permutation()[i]` of the original one.
+    pub fn row_permutation(&self) -> &[usize] {
+        &self.row_permutation
+    }
+
+    /// Column `j` of the factorized matrix is column `column_permutation()[j]` of the original one.
+    pub fn column_permutation(&self) -> &[usize] {
+        &self.column_permutation
+    }
+
+    /// Largest entry met during elimination divided by the largest entry of the original matrix.
+    pub fn growth_factor(&self) -> f64 {
+        self.growth_factor
+    }
+}
+
+impl Factorization for PivotedLU {
+    fn solve(&self, b: &[f64]) -> Result<Vec<f64>, SolveError> {
+        let width = self.width;
+        if b.len() != width {
+            return Err(SolveError::DimensionMismatch {
+                expected: width,
+                found: b.len(),
+            });
+        }
+
+        let mut z: Vec<f64> = self.row_permutation.iter().map(|&i| b[i]).collect();
+        for i in 0..width {
+            for j in 0..i {
+                z[i] -= self.lu[i * width + j] * z[j];
+            }
+        }
+        for i in (0..width).rev() {
+            for j in i + 1..width {
+                z[i] -= self.lu[i * width + j] * z[j];
+            }
+            z[i] /= self.lu[i * width + i];
+        }
+
+        let mut x: Vec<f64> = (0..width).map(|_| 0.0).collect();
+        for (k, &j) in self.column_permutation.iter().enumerate() {
+            x[j] = z[k];
+        }
+
+        Ok(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-12, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn full_pivoting_swaps_rows_and_columns() {
+        let mat = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 10.0];
+        let b = [1.0, -2.0, 0.5];
+
+        let lu = PivotedLUSolver::new(Pivoting::Full)
+            .factorize(&mat, 3)
+            .unwrap();
+        assert_eq!(lu.row_permutation()[0], 2);
+        assert_eq!(lu.column_permutation()[0], 2);
+        assert_close(
+            &lu.solve(&b).unwrap(),
+            &LUSolver.solve(&mat, 3, &b).unwrap(),
+        );
+
+        let partial = PivotedLUSolver::new(Pivoting::Partial);
+        assert_close(
+            &LinearEquationSolver::solve(&partial, &mat, 3, &b).unwrap(),
+            &LUSolver.solve(&mat, 3, &b).unwrap(),
+        );
+    }
+
+    #[test]
+    fn wilkinson_matrix_grows_by_powers_of_two() {
+        // 1 on the diagonal and in the last column, -1 below the diagonal: partial
+        // pivoting never swaps and doubles the last column at every step.
+        let width = 6;
+        let mut mat = vec![0.0; width * width];
+        for i in 0..width {
+            for j in 0..width {
+                mat[i * width + j] = match (i, j) {
+                    _ if i == j || j == width - 1 => 1.0,
+                    _ if j < i => -1.0,
+                    _ => 0.0,
+                };
+            }
+        }
+
+        let lu = PivotedLUSolver::new(Pivoting::Partial)
+            .factorize(&mat, width)
+            .unwrap();
+        assert_eq!(lu.row_permutation(), &[0, 1, 2, 3, 4, 5]);
+        assert_eq!(lu.growth_factor(), 32.0);
+
+        let b: Vec<f64> = (0..width).map(|i| i as f64).collect();
+        assert_close(
+            &lu.solve(&b).unwrap(),
+            &LUSolver.solve(&mat, width, &b).unwrap(),
+        );
+    }
+
+    #[test]
+    fn reused_factorization_matches_fresh_solves() {
+        let mat = [4.0, -1.0, 0.5, 2.0, 5.0, 1.0, -1.0, 0.0, 3.0];
+        let solvers: [&dyn LinearEquationSolver; 3] = [
+            &LUSolver,
+            &PivotedLUSolver::new(Pivoting::Partial),
+            &PivotedLUSolver::new(Pivoting::Full),
+        ];
+        for solver in solvers {
+            let factorization = solver.factorize(&mat, 3).unwrap();
+            for b in [[1.0, 0.0, 0.0], [0.0, 2.0, -1.0], [3.0, 1.0, 4.0]] {
+                assert_close(
+                    &factorization.solve(&b).unwrap(),
+                    &solver.solve(&mat, 3, &b).unwrap(),
+                );
+            }
+        }
     }
 }
@@ -1,165 +1,221 @@
-use integrals::TrapezoidIntegrator;
-use linear_equation::LUSolver;
-use testing::test_method;
+use std::collections::HashSet;
 
-use galerkin::GalerkinMethod;
-use galerkin_fourier::GalerkinMethodWithFourier;
-use neumann::NeumannMethod;
-use nystrom::NystromMethod;
+use nikitin_bio_bmk3::{
+    method::Method,
+    problem::{catalog, Problem},
+    registry::{method_entry, METHODS},
+    testing::{print_summary, run_problems},
+};
 
-mod functions;
-mod galerkin;
-mod galerkin_fourier;
-mod integrals;
-mod linear_equation;
-mod method;
-mod neumann;
-mod nystrom;
-mod testing;
+const USAGE: &str = "usage: nikitin-bio-bmk3 [options] [problem.toml ...]
 
-fn main() {
-    let a = 1.0;
-    let b = 1.0;
-    let y = 2.0 / 3.0 * b + 52.0 / 27.0 * a;
-    let q =
-        |x: f64| 1.0 / 3.0 * a * x * x - 16.0 / 9.0 * a * x.abs() + 56.0 / 27.0 * a + 1.0 / 3.0 * b;
-    let r = |x: f64| a * x * x + b;
-    let m = |x: f64| (-2.0 * x.abs()).exp();
-    let w = |x: f64| (-(x.abs())).exp() * q(x) / (1.0 + (-(x.abs())).exp() * r(x));
-    let c = |x: f64| 1.0 + (-(x.abs())).exp() * r(x);
+Runs the selected methods on the selected problems and writes the solutions, differences
+and statistics to the output directory.
 
-    let width = 15.0;
-    let node_count = 5000;
-    let comparison_point_count = 5000;
+options:
+    -l, --list                  list the methods with their parameters and the built-in problems
+    -m, --methods a,b,...       methods to run, all by default
+    -p, --problems a,b,...      built-in problems to run, all by default unless files are given
+    -s, --set [method.]key=v    override a method parameter, without a method for every
+                                selected method that has it, can be repeated
+    -o, --out-dir dir           output directory, `results` by default
+    -j, --threads n             worker threads, all cores by default
+    -c, --points n              comparison points on [0, width], 5000 by default
+    -h, --help                  show this message";
 
-    let nystrom = NystromMethod::new(Box::new(LUSolver), node_count);
-    let galerkin = GalerkinMethod::new(
-        Box::new(TrapezoidIntegrator::new(node_count / 4)),
-        Box::new(LUSolver),
-        Box::new(|t: f64| (1.0f64 - t.powi(2)).sqrt()),
-        60,
-    );
-    let galerkin_fourier = GalerkinMethodWithFourier::new(
-        Box::new(TrapezoidIntegrator::new(node_count / 10)),
-        Box::new(LUSolver),
-        Box::new(|t: f64| (1.0f64 - t.powi(2)).sqrt()),
-        400,
-    );
-    let neumann = NeumannMethod::new(
-        500,
-        node_count,
-        Box::new(TrapezoidIntegrator::new(node_count)),
-    );
+struct Options {
+    list: bool,
+    methods: Option<Vec<String>>,
+    problems: Option<Vec<String>>,
+    /// Method, or all that have the parameter, parameter and value.
+    overrides: Vec<(Option<String>, String, f64)>,
+    out_dir: String,
+    threads: Option<usize>,
+    comparison_point_count: usize,
+    files: Vec<String>,
+}
 
-    let prefix = "exponent";
-    test_method(
-        &galerkin,
-        &m,
-        &w,
-        y,
-        &c,
-        comparison_point_count,
-        width,
-        "galerkin_taylor",
-        "results",
-        prefix,
-    );
-    test_method(
-        &galerkin_fourier,
-        &m,
-        &w,
-        y,
-        &c,
-        comparison_point_count,
-        width,
-        "galerkin_fourier",
-        "results",
-        prefix,
-    );
+fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        list: false,
+        methods: None,
+        problems: None,
+        overrides: vec![],
+        out_dir: "results".to_string(),
+        threads: None,
+        comparison_point_count: 5000,
+        files: vec![],
+    };
+    let names = |value: String| value.split(',').map(str::to_string).collect();
+    let count = |flag: &str, value: String, minimum: usize| match value.parse::<usize>() {
+        Ok(count) if count >= minimum => Ok(count),
+        _ => Err(format!(
+            "{flag} expects an integer of at least {minimum}, got '{value}'"
+        )),
+    };
 
-    test_method(
-        &neumann,
-        &m,
-        &w,
-        y,
-        &c,
-        comparison_point_count,
-        width,
-        "neumann",
-        "results",
-        prefix,
-    );
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{arg} expects a value"));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                std::process::exit(0);
+            }
+            "-l" | "--list" => options.list = true,
+            "-m" | "--methods" => options.methods = Some(names(value()?)),
+            "-p" | "--problems" => options.problems = Some(names(value()?)),
+            "-s" | "--set" => {
+                let setting = value()?;
+                let (key, number) = setting
+                    .split_once('=')
+                    .ok_or(format!("--set expects key=value, got '{setting}'"))?;
+                let number = number
+                    .parse()
+                    .map_err(|_| format!("'{number}' in --set {setting} is not a number"))?;
+                let (method, key) = match key.split_once('.') {
+                    Some((method, key)) => (Some(method.to_string()), key),
+                    None => (None, key),
+                };
+                options.overrides.push((method, key.to_string(), number));
+            }
+            "-o" | "--out-dir" => options.out_dir = value()?,
+            "-j" | "--threads" => options.threads = Some(count(&arg, value()?, 1)?),
+            "-c" | "--points" => options.comparison_point_count = count(&arg, value()?, 2)?,
+            _ if arg.starts_with('-') => return Err(format!("unknown option '{arg}'")),
+            _ => options.files.push(arg),
+        }
+    }
+    Ok(options)
+}
 
-    test_method(
-        &nystrom,
-        &m,
-        &w,
-        y,
-        &c,
-        comparison_point_count,
-        width,
-        "nystrom",
-        "results",
-        prefix,
-    );
+fn list() {
+    println!("methods:");
+    for entry in &METHODS {
+        let parameters: Vec<String> = entry
+            .parameters
+            .iter()
+            .map(|(name, default)| match default.fract() {
+                0.0 => format!("{name}={default}"),
+                _ => format!("{name}={default:e}"),
+            })
+            .collect();
+        println!("    {:<26}{}", entry.name, parameters.join(" "));
+    }
+    println!("problems:");
+    for problem in catalog() {
+        println!(
+            "    {:<26}width={} parameter={}",
+            problem.name, problem.width, problem.parameter
+        );
+    }
+}
 
-    let p = 1.0;
-    let a = 1.0;
-    let n = 2;
-    let y = a * std::f64::consts::PI * (a + 5.0 * p * p) * (a + 8.0 * p * p)
-        / (p * (a * a + 21.0 * a * p * p + 120.0 * p * p * p * p));
-    let m = |x: f64| p / (x * x + p * p) * std::f64::consts::FRAC_1_PI;
-    let w = |x: f64| a / (x * x + (n as f64 + 1.0).powi(2) * p * p);
-    let c = |x: f64| 1.0 + 24.0 / (71.0 * (x * x + 1.0)) + 40.0 / (71.0 * (x * x + 4.0));
-    let prefix = "rational";
-    test_method(
-        &galerkin,
-        &m,
-        &w,
-        y,
-        &c,
-        comparison_point_count,
-        width,
-        "galerkin_taylor",
-        "results",
-        prefix,
-    );
-    test_method(
-        &galerkin_fourier,
-        &m,
-        &w,
-        y,
-        &c,
-        comparison_point_count,
-        width,
-        "galerkin_fourier",
-        "results",
-        prefix,
-    );
+/// The first name that occurs a second time.
+fn first_duplicate<'a>(mut names: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let mut seen = HashSet::new();
+    names.find(|&name| !seen.insert(name))
+}
 
-    test_method(
-        &neumann,
-        &m,
-        &w,
-        y,
-        &c,
-        comparison_point_count,
-        width,
-        "neumann",
-        "results",
-        prefix,
-    );
+fn run(options: Options) -> Result<(), String> {
+    if let Some(threads) = options.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .map_err(|error| error.to_string())?;
+    }
+
+    if let Some(name) = first_duplicate(options.methods.iter().flatten().map(String::as_str)) {
+        return Err(format!("method '{name}' is given more than once"));
+    }
+    let entries = match &options.methods {
+        Some(names) => names
+            .iter()
+            .map(|name| method_entry(name).map_err(|error| error.to_string()))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => METHODS.iter().collect(),
+    };
+    for (method, key, _) in &options.overrides {
+        let applies = entries.iter().any(|entry| {
+            method.as_ref().is_none_or(|method| method == entry.name) && entry.has_parameter(key)
+        });
+        if !applies {
+            let target = method.as_deref().unwrap_or("any selected method");
+            return Err(format!("'{key}' is not a parameter of {target}"));
+        }
+    }
+    let built = entries
+        .iter()
+        .map(|entry| {
+            let overrides: Vec<(String, f64)> = options
+                .overrides
+                .iter()
+                .filter(|(method, key, _)| match method {
+                    Some(method) => method == entry.name,
+                    None => entry.has_parameter(key),
+                })
+                .map(|(_, key, value)| (key.clone(), *value))
+                .collect();
+            entry.build(&overrides).map_err(|error| error.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let methods: Vec<(&str, &dyn Method)> = entries
+        .iter()
+        .zip(&built)
+        .map(|(entry, method)| (entry.name, method.as_ref()))
+        .collect();
 
-    test_method(
-        &nystrom,
-        &m,
-        &w,
-        y,
-        &c,
-        comparison_point_count,
-        width,
-        "nystrom",
-        "results",
-        prefix,
+    let mut problems = vec![];
+    if options.problems.is_some() || options.files.is_empty() {
+        let mut available = catalog();
+        match &options.problems {
+            Some(names) => {
+                if let Some(name) = first_duplicate(names.iter().map(String::as_str)) {
+                    return Err(format!("problem '{name}' is given more than once"));
+                }
+                for name in names {
+                    let i = available
+                        .iter()
+                        .position(|problem| &problem.name == name)
+                        .ok_or(format!("unknown problem '{name}'"))?;
+                    problems.push(available.swap_remove(i));
+                }
+            }
+            None => problems = available,
+        }
+    }
+    for path in &options.files {
+        problems.push(Problem::load(path).map_err(|error| format!("{path}: {error}"))?);
+    }
+    // Results are written under the problem name, so a second problem of the same name
+    // would overwrite the first.
+    if let Some(name) = first_duplicate(problems.iter().map(|problem| problem.name.as_str())) {
+        return Err(format!("problem '{name}' is given more than once"));
+    }
+
+    std::fs::create_dir_all(&options.out_dir).map_err(|error| error.to_string())?;
+    let summaries = run_problems(
+        &methods,
+        &problems,
+        options.comparison_point_count,
+        &options.out_dir,
     );
+    println!();
+    print_summary(&summaries);
+    Ok(())
+}
+
+fn main() {
+    let options = parse_options(std::env::args().skip(1)).unwrap_or_else(|error| {
+        eprintln!("{error}\n\n{USAGE}");
+        std::process::exit(2);
+    });
+
+    if options.list {
+        list();
+        return;
+    }
+    if let Err(error) = run(options) {
+        eprintln!("{error}");
+        std::process::exit(1);
+    }
 }
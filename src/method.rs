@@ -1,11 +1,168 @@
-use crate::functions::Function;
+use std::fmt;
+
+use crate::functions::{EvenFunction, Function};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveError {
+    /// A zero pivot was met while eliminating the given column.
+    SingularMatrix { column: usize },
+    /// The matrix or right-hand side does not have the size the solver expects.
+    DimensionMismatch { expected: usize, found: usize },
+    /// The assembled system has a NaN or infinite entry in the given row.
+    NonFiniteKernel { row: usize },
+    /// The Neumann iterates stopped being finite at the given iteration.
+    NeumannDivergence { iteration: usize },
+    /// The estimated spectral radius of the integral operator is at least 1, so plain
+    /// fixed-point iteration cannot converge.
+    NotContracting { spectral_radius: f64 },
+    /// An iterative solver reached its iteration limit with the given relative residual.
+    NotConverged { iterations: usize, residual: f64 },
+    /// The method needs a finite `width`, but got an infinite one.
+    UnboundedDomain,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::SingularMatrix { column } => {
+                write!(f, "singular system: zero pivot in column {column}")
+            }
+            SolveError::DimensionMismatch { expected, found } => {
+                write!(f, "dimension mismatch: expected {expected}, found {found}")
+            }
+            SolveError::NonFiniteKernel { row } => {
+                write!(f, "non-finite kernel value in row {row}")
+            }
+            SolveError::NeumannDivergence { iteration } => {
+                write!(f, "Neumann series diverged at iteration {iteration}")
+            }
+            SolveError::NotContracting { spectral_radius } => write!(
+                f,
+                "fixed-point iteration cannot converge, spectral radius estimate is {spectral_radius}"
+            ),
+            SolveError::NotConverged {
+                iterations,
+                residual,
+            } => write!(
+                f,
+                "no convergence after {iterations} iterations, relative residual {residual}"
+            ),
+            SolveError::UnboundedDomain => write!(f, "method needs a finite width"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// What a method can report about a solve besides the solution.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diagnostics {
+    /// Iterations actually used.
+    pub iterations: Option<usize>,
+    /// Average factor by which the error shrank per iteration.
+    pub contraction_factor: Option<f64>,
+    /// Estimated norm of the integral operator, measured before iterating.
+    pub operator_norm: Option<f64>,
+    /// Signs of trouble that did not stop the solve.
+    pub warnings: Vec<String>,
+}
 
 pub trait Method {
-    fn solve(
-        &self,
-        birth_probability: &dyn Function,
-        death_probability: &dyn Function,
+    /// `width` is where the equation is truncated to `[-width, width]`, methods that can solve
+    /// on the whole line accept `f64::INFINITY` and the rest return `SolveError::UnboundedDomain`.
+    fn solve<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<Box<dyn Function + 'a>, SolveError>;
+
+    /// Like `solve`, and also reports how the solve went. Only iterative methods have
+    /// anything to report, the rest keep this default.
+    ///
+    /// An iterative method that stops at its iteration limit before meeting its tolerance
+    /// still returns its last iterate, with a warning in `Diagnostics::warnings`, since
+    /// that iterate is usually close. Errors are for solves that give nothing usable.
+    fn solve_with_diagnostics<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
         parameter: f64,
         width: f64,
-    ) -> Box<dyn Function>;
+    ) -> Result<(Box<dyn Function + 'a>, Diagnostics), SolveError> {
+        let solution = self.solve(birth_probability, death_probability, parameter, width)?;
+        Ok((solution, Diagnostics::default()))
+    }
+
+    /// Whether the solutions `solve` returns are already even, so they are right for
+    /// negative `x` too. The equation is even whenever `birth_probability` and
+    /// `death_probability` are, but methods working on `[0, width]` only keep `false`.
+    fn even_solution(&self) -> bool {
+        false
+    }
+
+    /// Like `solve_with_diagnostics`, but the solution is made even with `EvenFunction`
+    /// unless the method declares it already is.
+    fn solve_even<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<(Box<dyn Function + 'a>, Diagnostics), SolveError> {
+        let (solution, diagnostics) =
+            self.solve_with_diagnostics(birth_probability, death_probability, parameter, width)?;
+        if self.even_solution() {
+            Ok((solution, diagnostics))
+        } else {
+            Ok((Box::new(EvenFunction::new(solution)), diagnostics))
+        }
+    }
+
+    /// Solves the equation for each parameter in turn. Methods whose system matrix does not
+    /// depend on the parameter override this to factorize it only once.
+    fn solve_parameters<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameters: &[f64],
+        width: f64,
+    ) -> Result<Vec<Box<dyn Function + 'a>>, SolveError> {
+        parameters
+            .iter()
+            .map(|&parameter| self.solve(birth_probability, death_probability, parameter, width))
+            .collect()
+    }
+}
+
+pub(crate) fn check_finite(mat: &[f64], width: usize) -> Result<(), SolveError> {
+    match mat.iter().position(|v| !v.is_finite()) {
+        Some(i) => Err(SolveError::NonFiniteKernel { row: i / width }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        integrals::TrapezoidIntegrator, linear_equation::LUSolver, nystrom::NystromMethod,
+    };
+
+    #[test]
+    fn reports_the_row_of_a_non_finite_entry() {
+        assert_eq!(check_finite(&[1.0, 2.0, 3.0, 4.0], 2), Ok(()));
+        assert_eq!(
+            check_finite(&[1.0, 2.0, 3.0, f64::NAN], 2),
+            Err(SolveError::NonFiniteKernel { row: 1 })
+        );
+    }
+
+    #[test]
+    fn non_finite_kernel_is_an_error() {
+        let method = NystromMethod::new(Box::new(LUSolver), Box::new(TrapezoidIntegrator::new(11)));
+        let result = method.solve(&|x: f64| 1.0 / x, &|_: f64| 0.0, 1.0, 1.0);
+        assert!(matches!(result, Err(SolveError::NonFiniteKernel { .. })));
+    }
 }
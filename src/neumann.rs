@@ -1,54 +1,577 @@
-use crate::{functions::*, integrals::Integrator, method::Method};
+use crate::{
+    convolution::ConvolutionOperator,
+    functions::*,
+    integrals::{Integrator, TrapezoidIntegrator},
+    interpolation::{Extrapolation, Interpolation, PointFunction},
+    krylov::{GmresSolver, IterativeSolver},
+    linear_equation::{LUSolver, LinearEquationSolver},
+    method::{Diagnostics, Method, SolveError},
+};
 use rayon::prelude::*;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Acceleration {
+    /// Plain Picard sweeps `u <- K u + f`.
+    None,
+    /// The vector form of Aitken's Δ² by Irons and Tuck, applied after every two sweeps.
+    Aitken,
+    /// Anderson mixing of the last `depth` sweeps.
+    Anderson { depth: usize },
+    /// Restarted GMRES on `(I - K) u = f`, one sweep per operator product. The tolerance is
+    /// then on the relative residual.
+    Gmres { restart: usize },
+}
+
+/// Fixed-point iteration on the node values of `u = c - 1`, stopped once the sup-norm of
+/// the change made by a sweep drops below `tolerance`. If `max_iterations` runs out first,
+/// the last iterate is returned with a warning in its diagnostics.
 pub struct NeumannMethod {
-    iter_count: usize,
+    max_iterations: usize,
+    tolerance: f64,
     node_count: usize,
-    integrator: Box<dyn Integrator>,
+    /// `None` applies the kernel with a `ConvolutionOperator` instead.
+    integrator: Option<Box<dyn Integrator>>,
+    acceleration: Acceleration,
+    interpolation: Interpolation,
+    extrapolation: Extrapolation,
 }
 
 impl NeumannMethod {
-    pub fn new(iter_count: usize, node_count: usize, integrator: Box<dyn Integrator>) -> Self {
+    pub fn new(
+        max_iterations: usize,
+        tolerance: f64,
+        node_count: usize,
+        integrator: Box<dyn Integrator>,
+        acceleration: Acceleration,
+    ) -> Self {
+        Self {
+            max_iterations,
+            tolerance,
+            node_count,
+            integrator: Some(integrator),
+            acceleration,
+            interpolation: Interpolation::Linear,
+            extrapolation: Extrapolation::EvenReflection,
+        }
+    }
+
+    /// Sweeps with the trapezoid rule on the nodes, applied by FFT in `O(n log n)`.
+    pub fn convolution(
+        max_iterations: usize,
+        tolerance: f64,
+        node_count: usize,
+        acceleration: Acceleration,
+    ) -> Self {
         Self {
-            iter_count,
+            max_iterations,
+            tolerance,
             node_count,
-            integrator,
+            integrator: None,
+            acceleration,
+            interpolation: Interpolation::Linear,
+            extrapolation: Extrapolation::EvenReflection,
         }
     }
+
+    /// How the returned solution is interpolated from the node values, linearly and
+    /// mirrored to negative `x` unless set here.
+    pub fn with_interpolation(
+        mut self,
+        interpolation: Interpolation,
+        extrapolation: Extrapolation,
+    ) -> Self {
+        self.interpolation = interpolation;
+        self.extrapolation = extrapolation;
+        self
+    }
 }
 
-impl Method for NeumannMethod {
-    fn solve(
+fn sup_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0, f64::max)
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn difference(a: &[f64], b: &[f64]) -> Vec<f64> {
+    a.iter().zip(b).map(|(a, b)| a - b).collect()
+}
+
+/// The sup-norms of `G(u) - u` seen so far, `G` being one sweep.
+struct Increments(Vec<f64>);
+
+impl Increments {
+    fn push(&mut self, sweep: &[f64], previous: &[f64]) -> Result<f64, SolveError> {
+        if sweep.iter().any(|v| !v.is_finite()) {
+            return Err(SolveError::NeumannDivergence {
+                iteration: self.0.len(),
+            });
+        }
+        let increment = sup_distance(sweep, previous);
+        self.0.push(increment);
+        Ok(increment)
+    }
+
+    /// Geometric mean of the ratios of successive increments.
+    fn contraction_factor(&self) -> Option<f64> {
+        let (first, last) = (self.0.first()?, self.0.last()?);
+        if self.0.len() < 2 || *first == 0.0 {
+            return None;
+        }
+        Some((last / first).powf(1.0 / (self.0.len() - 1) as f64))
+    }
+
+    /// The first sweep after which the increment grew `GROWTH_RUN` times in a row.
+    fn growth(&self) -> Option<usize> {
+        self.0
+            .windows(GROWTH_RUN + 1)
+            .position(|window| window.windows(2).all(|pair| pair[1] > pair[0]))
+    }
+
+    /// Accelerated iterates are not monotone, so growth is only flagged for plain sweeps.
+    fn diagnostics(&self, flag_growth: bool, tolerance: f64) -> Diagnostics {
+        let mut warnings: Vec<String> = self
+            .growth()
+            .filter(|_| flag_growth)
+            .map(|sweep| format!("increments grew for {GROWTH_RUN} sweeps after sweep {sweep}"))
+            .into_iter()
+            .collect();
+        if let Some(&last) = self.0.last().filter(|&&last| last > tolerance) {
+            warnings.push(format!(
+                "tolerance {tolerance} not met after {} sweeps, last increment {last}",
+                self.0.len()
+            ));
+        }
+        Diagnostics {
+            iterations: Some(self.0.len()),
+            contraction_factor: self.contraction_factor(),
+            warnings,
+            ..Default::default()
+        }
+    }
+}
+
+/// Applies the discretized integral operator to node values.
+type Kernel<'a> = Box<dyn Fn(&[f64]) -> Vec<f64> + Sync + 'a>;
+
+const GROWTH_RUN: usize = 3;
+const POWER_ITERATIONS: usize = 30;
+
+impl NeumannMethod {
+    fn picard(
         &self,
+        sweep: &dyn Fn(&[f64]) -> Vec<f64>,
+        mut v: Vec<f64>,
+    ) -> Result<(Vec<f64>, Increments), SolveError> {
+        let mut increments = Increments(Vec::new());
+        while increments.0.len() < self.max_iterations {
+            let next = sweep(&v);
+            let increment = increments.push(&next, &v)?;
+            v = next;
+            if increment <= self.tolerance {
+                break;
+            }
+        }
+        Ok((v, increments))
+    }
+
+    fn aitken(
+        &self,
+        sweep: &dyn Fn(&[f64]) -> Vec<f64>,
+        mut v: Vec<f64>,
+    ) -> Result<(Vec<f64>, Increments), SolveError> {
+        let mut increments = Increments(Vec::new());
+        while increments.0.len() + 2 <= self.max_iterations {
+            let v1 = sweep(&v);
+            if increments.push(&v1, &v)? <= self.tolerance {
+                return Ok((v1, increments));
+            }
+            let v2 = sweep(&v1);
+            if increments.push(&v2, &v1)? <= self.tolerance {
+                return Ok((v2, increments));
+            }
+
+            let d2 = difference(&v2, &v1);
+            let dd = difference(&d2, &difference(&v1, &v));
+            let denominator = dot(&dd, &dd);
+            v = if denominator == 0.0 {
+                v2
+            } else {
+                let step = dot(&d2, &dd) / denominator;
+                v2.iter().zip(&d2).map(|(v, d)| v - step * d).collect()
+            };
+        }
+        Ok((v, increments))
+    }
+
+    fn anderson(
+        &self,
+        sweep: &dyn Fn(&[f64]) -> Vec<f64>,
+        mut v: Vec<f64>,
+        depth: usize,
+    ) -> Result<(Vec<f64>, Increments), SolveError> {
+        let mut increments = Increments(Vec::new());
+        // Differences of successive sweeps and of successive residuals `G(u) - u`.
+        let mut sweep_differences: Vec<Vec<f64>> = Vec::new();
+        let mut residual_differences: Vec<Vec<f64>> = Vec::new();
+        let mut previous: Option<(Vec<f64>, Vec<f64>)> = None;
+
+        while increments.0.len() < self.max_iterations {
+            let g = sweep(&v);
+            if increments.push(&g, &v)? <= self.tolerance {
+                return Ok((g, increments));
+            }
+            let residual = difference(&g, &v);
+
+            if let Some((previous_g, previous_residual)) = previous {
+                sweep_differences.push(difference(&g, &previous_g));
+                residual_differences.push(difference(&residual, &previous_residual));
+                if sweep_differences.len() > depth {
+                    sweep_differences.remove(0);
+                    residual_differences.remove(0);
+                }
+            }
+
+            // Least squares fit of the residual by the residual differences, through the
+            // normal equations, which are tiny.
+            let m = residual_differences.len();
+            let normal: Vec<f64> = (0..m * m)
+                .map(|i| dot(&residual_differences[i / m], &residual_differences[i % m]))
+                .collect();
+            let right: Vec<f64> = residual_differences
+                .iter()
+                .map(|d| dot(d, &residual))
+                .collect();
+
+            let gamma = (m > 0)
+                .then(|| LUSolver.solve(&normal, m, &right).ok())
+                .flatten()
+                .filter(|gamma| gamma.iter().all(|g| g.is_finite()));
+            v = match gamma {
+                Some(gamma) => {
+                    let mut next = g.clone();
+                    for (d, gamma) in sweep_differences.iter().zip(&gamma) {
+                        for (v, d) in next.iter_mut().zip(d) {
+                            *v -= gamma * d;
+                        }
+                    }
+                    next
+                }
+                None => {
+                    sweep_differences.clear();
+                    residual_differences.clear();
+                    g.clone()
+                }
+            };
+            previous = Some((g, residual));
+        }
+        Ok((v, increments))
+    }
+
+    fn gmres(
+        &self,
+        kernel: &dyn Fn(&[f64]) -> Vec<f64>,
+        b: &[f64],
+        restart: usize,
+    ) -> Result<(Vec<f64>, Diagnostics), SolveError> {
+        let operator = |v: &[f64]| difference(v, &kernel(v));
+        let solution =
+            GmresSolver::new(self.tolerance, restart, self.max_iterations).iterate(&operator, b)?;
+        if solution.x.iter().any(|v| !v.is_finite()) {
+            return Err(SolveError::NeumannDivergence {
+                iteration: solution.residual_history.len(),
+            });
+        }
+
+        let iterations = solution.residual_history.len();
+        let diagnostics = Diagnostics {
+            iterations: Some(iterations),
+            contraction_factor: solution
+                .residual_history
+                .last()
+                .map(|residual| residual.powf(1.0 / iterations as f64)),
+            warnings: match (solution.converged, solution.residual_history.last()) {
+                (false, Some(residual)) => vec![format!(
+                    "tolerance {} not met after {iterations} iterations, relative residual {residual}",
+                    self.tolerance
+                )],
+                (false, None) => vec!["GMRES broke down before its first iteration".to_string()],
+                (true, _) => vec![],
+            },
+            ..Default::default()
+        };
+        Ok((solution.x, diagnostics))
+    }
+
+    /// Spectral radius of `kernel` by power iteration, a lower bound for its norm.
+    fn spectral_radius(&self, kernel: &dyn Fn(&[f64]) -> Vec<f64>) -> f64 {
+        let mut v: Vec<f64> = (0..self.node_count).map(|_| 1.0).collect();
+        let mut radius = 0.0;
+        for _ in 0..POWER_ITERATIONS {
+            let next = kernel(&v);
+            radius = next.iter().fold(0.0, |max, v| f64::max(max, v.abs()));
+            if radius == 0.0 || !radius.is_finite() {
+                break;
+            }
+            v = next.iter().map(|v| v / radius).collect();
+        }
+        radius
+    }
+
+    /// Interpolates `c = v + 1` from the nodes. The nodes are not Chebyshev points, so for
+    /// a Chebyshev interpolant one more sweep is taken at the Chebyshev points instead,
+    /// with the kernel integrated against the linear interpolant of `v`.
+    fn node_function(
+        &self,
+        v: Vec<f64>,
         birth_probability: &dyn Function,
         death_probability: &dyn Function,
         parameter: f64,
         width: f64,
     ) -> Box<dyn Function> {
-        let mut v: Vec<f64> = (0..self.node_count).map(|_| 0.0).collect();
-        let step = width / (self.node_count - 1) as f64;
-
-        for _ in 0..self.iter_count {
-            let c = |x: f64| v[(x / step) as usize];
-            v = (0..self.node_count)
-                .into_par_iter()
-                .map(|i| {
-                    let x = (i as f64) * step;
-
-                    (self.integrator.integrate(
-                        &|t| (birth_probability.get(t - x) + birth_probability.get(t + x)) * c(t),
+        match self.interpolation {
+            Interpolation::Chebyshev => {
+                let u = PointFunction::new(v, 0.0, width, Extrapolation::Clamp);
+                let trapezoid = TrapezoidIntegrator::new(self.node_count);
+                let integrator: &dyn Integrator = match &self.integrator {
+                    Some(integrator) => integrator.as_ref(),
+                    None => &trapezoid,
+                };
+                let sweep = |x: f64| {
+                    let integral = integrator.integrate(
+                        &|t| {
+                            (birth_probability.get(t - x) + birth_probability.get(t + x)) * u.get(t)
+                        },
                         0.0,
                         width,
-                    ) + (birth_probability.get(x) * parameter - death_probability.get(x)))
+                    );
+                    1.0 + (birth_probability.get(x) * parameter - death_probability.get(x)
+                        + integral)
                         / (1.0 + death_probability.get(x))
-                })
-                .collect();
+                };
+                Interpolation::Chebyshev.tabulate(
+                    &sweep,
+                    0.0,
+                    width,
+                    self.node_count,
+                    self.extrapolation,
+                )
+            }
+            interpolation => interpolation.from_values(
+                v.iter().map(|v| v + 1.0).collect(),
+                0.0,
+                width,
+                self.extrapolation,
+            ),
+        }
+    }
+}
+
+impl Method for NeumannMethod {
+    fn solve<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<Box<dyn Function + 'a>, SolveError> {
+        self.solve_with_diagnostics(birth_probability, death_probability, parameter, width)
+            .map(|(solution, _)| solution)
+    }
+
+    fn solve_with_diagnostics<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<(Box<dyn Function + 'a>, Diagnostics), SolveError> {
+        if !width.is_finite() {
+            return Err(SolveError::UnboundedDomain);
+        }
+        let step = width / (self.node_count - 1) as f64;
+
+        // `max_x ∫|k(x, t)| dt` bounds the operator norm.
+        let (kernel, l1_bound): (Kernel, f64) = match &self.integrator {
+            Some(integrator) => {
+                let l1_bound = (0..self.node_count)
+                    .into_par_iter()
+                    .map(|i| {
+                        let x = (i as f64) * step;
+                        integrator.integrate(
+                            &|t| {
+                                (birth_probability.get(t - x) + birth_probability.get(t + x)).abs()
+                            },
+                            0.0,
+                            width,
+                        ) / (1.0 + death_probability.get(x)).abs()
+                    })
+                    .reduce(|| 0.0, f64::max);
+                let kernel = move |v: &[f64]| -> Vec<f64> {
+                    let c = PointFunction::new(v.to_vec(), 0.0, width, Extrapolation::Clamp);
+                    (0..self.node_count)
+                        .into_par_iter()
+                        .map(|i| {
+                            let x = (i as f64) * step;
+
+                            integrator.integrate(
+                                &|t| {
+                                    (birth_probability.get(t - x) + birth_probability.get(t + x))
+                                        * c.get(t)
+                                },
+                                0.0,
+                                width,
+                            ) / (1.0 + death_probability.get(x))
+                        })
+                        .collect()
+                };
+                (Box::new(kernel), l1_bound)
+            }
+            None => {
+                let absolute = |x: f64| birth_probability.get(x).abs();
+                let ones: Vec<f64> = (0..self.node_count).map(|_| 1.0).collect();
+                let l1_bound =
+                    ConvolutionOperator::new(&absolute, death_probability, self.node_count, width)?
+                        .apply(&ones)
+                        .iter()
+                        .fold(0.0, |max, v| f64::max(max, v.abs()));
+                let convolution = ConvolutionOperator::new(
+                    birth_probability,
+                    death_probability,
+                    self.node_count,
+                    width,
+                )?;
+                (Box::new(move |v: &[f64]| convolution.apply(v)), l1_bound)
+            }
+        };
+        let b: Vec<f64> = (0..self.node_count)
+            .into_par_iter()
+            .map(|i| {
+                let x = (i as f64) * step;
+                (birth_probability.get(x) * parameter - death_probability.get(x))
+                    / (1.0 + death_probability.get(x))
+            })
+            .collect();
+        let sweep =
+            |v: &[f64]| -> Vec<f64> { kernel(v).iter().zip(&b).map(|(kv, b)| kv + b).collect() };
+        let start: Vec<f64> = (0..self.node_count).map(|_| 0.0).collect();
+
+        // Only when the L1 bound does not prove convergence is the spectral radius
+        // estimated, at the price of a few sweeps.
+        let operator_norm = if l1_bound < 1.0 {
+            l1_bound
+        } else {
+            self.spectral_radius(&kernel)
+        };
+        let contracting = operator_norm < 1.0;
+        if !contracting && matches!(self.acceleration, Acceleration::None | Acceleration::Aitken) {
+            return Err(SolveError::NotContracting {
+                spectral_radius: operator_norm,
+            });
+        }
+
+        let (v, mut diagnostics) = match self.acceleration {
+            Acceleration::None => {
+                let (v, increments) = self.picard(&sweep, start)?;
+                (v, increments.diagnostics(true, self.tolerance))
+            }
+            Acceleration::Aitken => {
+                let (v, increments) = self.aitken(&sweep, start)?;
+                (v, increments.diagnostics(false, self.tolerance))
+            }
+            Acceleration::Anderson { depth } => {
+                let (v, increments) = self.anderson(&sweep, start, depth)?;
+                (v, increments.diagnostics(false, self.tolerance))
+            }
+            Acceleration::Gmres { restart } => self.gmres(&kernel, &b, restart)?,
+        };
+        diagnostics.operator_norm = Some(operator_norm);
+        if !contracting {
+            diagnostics.warnings.push(format!(
+                "operator norm estimate {operator_norm} is not below 1, only the acceleration converged"
+            ));
         }
 
-        Box::new(PointFunction::new(
-            v.iter().map(|v| v + 1.0).collect(),
-            0.0,
-            width,
-        ))
+        let solution =
+            self.node_function(v, birth_probability, death_probability, parameter, width);
+        Ok((solution, diagnostics))
+    }
+
+    fn even_solution(&self) -> bool {
+        self.extrapolation.is_even()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::problem::catalog;
+
+    #[test]
+    fn unmet_tolerance_returns_the_last_iterate() {
+        let problem = catalog().remove(0);
+        let accelerations = [
+            Acceleration::None,
+            Acceleration::Aitken,
+            Acceleration::Anderson { depth: 3 },
+            Acceleration::Gmres { restart: 2 },
+        ];
+        for acceleration in accelerations {
+            let method = NeumannMethod::convolution(4, 1e-14, 201, acceleration);
+            let (solution, diagnostics) = method
+                .solve_with_diagnostics(
+                    problem.birth_probability.as_ref(),
+                    problem.death_probability.as_ref(),
+                    problem.parameter,
+                    problem.width,
+                )
+                .unwrap();
+
+            assert_eq!(diagnostics.iterations, Some(4), "{acceleration:?}");
+            assert!(diagnostics.contraction_factor.is_some(), "{acceleration:?}");
+            assert!(
+                diagnostics
+                    .warnings
+                    .iter()
+                    .any(|warning| warning.contains("not met")),
+                "{acceleration:?}"
+            );
+            assert!(solution.get(1.0).is_finite());
+        }
+    }
+
+    #[test]
+    fn chebyshev_output_samples_the_iterate() {
+        let problem = catalog().remove(1);
+        let actual = problem.solution.as_deref().unwrap();
+        let max_error = |interpolation| {
+            let method =
+                NeumannMethod::convolution(200, 1e-13, 51, Acceleration::Anderson { depth: 5 })
+                    .with_interpolation(interpolation, Extrapolation::EvenReflection);
+            let solution = method
+                .solve(
+                    problem.birth_probability.as_ref(),
+                    problem.death_probability.as_ref(),
+                    problem.parameter,
+                    problem.width,
+                )
+                .unwrap();
+            (0..=300)
+                .map(|i| problem.width * i as f64 / 300.0)
+                .map(|x| (solution.get(x) - actual.get(x)).abs())
+                .fold(0.0, f64::max)
+        };
+
+        // The extra sweep is as accurate as the quadrature, which the linear interpolant
+        // between the nodes is not.
+        let (linear, chebyshev) = (
+            max_error(Interpolation::Linear),
+            max_error(Interpolation::Chebyshev),
+        );
+        assert!(chebyshev < linear / 2.0, "{chebyshev} vs {linear}");
     }
 }
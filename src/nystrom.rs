@@ -1,69 +1,403 @@
 use rayon::prelude::*;
 
-use crate::{functions::*, linear_equation::LinearEquationSolver, method::Method};
+use crate::{
+    functions::*,
+    integrals::QuadratureRule,
+    interpolation::{Extrapolation, Interpolation},
+    krylov::IterativeSolver,
+    linear_equation::{Factorization, LinearEquationSolver},
+    method::{check_finite, Method, SolveError},
+};
+
+enum Solver {
+    Dense(Box<dyn LinearEquationSolver>),
+    MatrixFree(Box<dyn IterativeSolver>),
+}
 
 pub struct NystromMethod {
-    solver: Box<dyn LinearEquationSolver>,
-    node_count: usize,
+    solver: Solver,
+    rule: Box<dyn QuadratureRule>,
+    tabulation: Option<(Interpolation, usize, Extrapolation)>,
 }
 
 impl NystromMethod {
-    pub fn new(solver: Box<dyn LinearEquationSolver>, node_count: usize) -> Self {
-        Self { solver, node_count }
+    pub fn new(solver: Box<dyn LinearEquationSolver>, rule: Box<dyn QuadratureRule>) -> Self {
+        Self {
+            solver: Solver::Dense(solver),
+            rule,
+            tabulation: None,
+        }
     }
-}
 
-impl Method for NystromMethod {
-    fn solve(
-        &self,
-        birth_probability: &dyn Function,
-        death_probability: &dyn Function,
-        parameter: f64,
+    /// Never assembles the system matrix, each product with it evaluates the kernel
+    /// afresh. That costs `O(n²)` kernel evaluations per iteration but only `O(n)` memory,
+    /// so the rule can have more nodes than a dense matrix would fit.
+    pub fn matrix_free(solver: Box<dyn IterativeSolver>, rule: Box<dyn QuadratureRule>) -> Self {
+        Self {
+            solver: Solver::MatrixFree(solver),
+            rule,
+            tabulation: None,
+        }
+    }
+
+    /// Returns the solution tabulated at `point_count` points of `[0, width]` and
+    /// interpolated, which is much cheaper to evaluate than the Nyström interpolant with
+    /// its sum over all nodes. An infinite width keeps the Nyström interpolant.
+    pub fn with_interpolation(
+        mut self,
+        interpolation: Interpolation,
+        point_count: usize,
+        extrapolation: Extrapolation,
+    ) -> Self {
+        self.tabulation = Some((interpolation, point_count, extrapolation));
+        self
+    }
+
+    /// Assembles and factorizes the system matrix, which depends only on the kernel,
+    /// the death term and the quadrature nodes, so it can be reused for any right-hand side.
+    /// A matrix-free method only keeps the nodes.
+    pub fn prepare<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
         width: f64,
-    ) -> Box<dyn Function> {
-        let k = |x, y| birth_probability.get(y - x) / (1.0 + death_probability.get(x));
-        let f = |x| {
+    ) -> Result<NystromSystem<'a>, SolveError> {
+        let (nodes, weights) = self.grid(width);
+        let n = nodes.len();
+
+        let factorization: Box<dyn Factorization + 'a> = match &self.solver {
+            Solver::Dense(solver) => {
+                let mat: Vec<f64> = nodes
+                    .par_iter()
+                    .enumerate()
+                    .flat_map_iter(|(j, &x)| {
+                        let scale = 1.0 / (1.0 + death_probability.get(x));
+                        nodes
+                            .iter()
+                            .zip(&weights)
+                            .enumerate()
+                            .map(move |(i, (&y, &weight))| {
+                                let k = (birth_probability.get(y - x)
+                                    + birth_probability.get(-y - x))
+                                    * scale
+                                    * weight;
+                                if i == j {
+                                    k - 1.0
+                                } else {
+                                    k
+                                }
+                            })
+                    })
+                    .collect();
+                check_finite(&mat, n)?;
+                solver.factorize(&mat, n)?
+            }
+            Solver::MatrixFree(solver) => {
+                let scales: Vec<f64> = nodes
+                    .par_iter()
+                    .map(|&x| 1.0 / (1.0 + death_probability.get(x)))
+                    .collect();
+                check_finite(&scales, 1)?;
+                Box::new(KernelOperator {
+                    birth_probability,
+                    nodes: nodes.clone(),
+                    weights: weights.clone(),
+                    scales,
+                    solver: solver.as_ref(),
+                })
+            }
+        };
+
+        Ok(NystromSystem {
+            birth_probability,
+            death_probability,
+            factorization,
+            nodes,
+            weights,
+            tabulation: self.tabulation,
+            width,
+        })
+    }
+
+    fn grid(&self, width: f64) -> (Vec<f64>, Vec<f64>) {
+        grid(self.rule.as_ref(), width)
+    }
+}
+
+/// Nodes and weights of the rule on `[0, width]`, the kernel is folded onto them by
+/// evenness. For an infinite width the rule is taken on `t ∈ [0, 1]` and mapped by
+/// `x = t / (1 - t)`, a node at `t = 1` is left out since the integrand vanishes there.
+pub(crate) fn grid(rule: &dyn QuadratureRule, width: f64) -> (Vec<f64>, Vec<f64>) {
+    if width.is_finite() {
+        rule.nodes_and_weights(0.0, width).into_iter().unzip()
+    } else {
+        rule.nodes_and_weights(0.0, 1.0)
+            .into_iter()
+            .filter(|&(t, _)| t < 1.0)
+            .map(|(t, weight)| (t / (1.0 - t), weight / (1.0 - t).powi(2)))
+            .unzip()
+    }
+}
+
+/// `Σ (m(y_i - x) + m(-y_i - x)) weighted_i` at each of `points`, the folded kernel
+/// applied to node values already multiplied by their weights, without `1 / (1 + w(x))`.
+pub(crate) fn apply_kernel(
+    birth_probability: &dyn Function,
+    nodes: &[f64],
+    weighted: &[f64],
+    points: &[f64],
+) -> Vec<f64> {
+    points
+        .par_iter()
+        .map(|&x| {
+            nodes
+                .iter()
+                .zip(weighted)
+                .map(|(&y, wu)| (birth_probability.get(y - x) + birth_probability.get(-y - x)) * wu)
+                .sum()
+        })
+        .collect()
+}
+
+/// `(K - I) u` for the folded kernel `K` on the nodes, applied without storing it.
+struct KernelOperator<'a> {
+    birth_probability: &'a dyn Function,
+    nodes: Vec<f64>,
+    weights: Vec<f64>,
+    /// `1 / (1 + w(x))` at each node.
+    scales: Vec<f64>,
+    solver: &'a dyn IterativeSolver,
+}
+
+impl Factorization for KernelOperator<'_> {
+    fn solve(&self, b: &[f64]) -> Result<Vec<f64>, SolveError> {
+        if b.len() != self.nodes.len() {
+            return Err(SolveError::DimensionMismatch {
+                expected: self.nodes.len(),
+                found: b.len(),
+            });
+        }
+
+        let operator = |u: &[f64]| {
+            let weighted: Vec<f64> = u.iter().zip(&self.weights).map(|(u, w)| u * w).collect();
+            apply_kernel(self.birth_probability, &self.nodes, &weighted, &self.nodes)
+                .iter()
+                .zip(&self.scales)
+                .zip(u)
+                .map(|((ku, scale), u)| ku * scale - u)
+                .collect()
+        };
+        Ok(self.solver.solve_operator(&operator, b)?.x)
+    }
+}
+
+pub struct NystromSystem<'a> {
+    birth_probability: &'a dyn Function,
+    death_probability: &'a dyn Function,
+    factorization: Box<dyn Factorization + 'a>,
+    nodes: Vec<f64>,
+    weights: Vec<f64>,
+    tabulation: Option<(Interpolation, usize, Extrapolation)>,
+    width: f64,
+}
+
+impl<'a> NystromSystem<'a> {
+    /// Solves `u(x) - ∫k(x, y) u(y) dy = f(x)` and returns `u` through Nyström interpolation.
+    pub fn solve_right_hand_side<'s>(
+        &'s self,
+        f: &'s dyn Function,
+    ) -> Result<Box<dyn Function + 's>, SolveError> {
+        let u = self.node_values(f)?;
+        Ok(Box::new(self.interpolant(u, move |x| f.get(x), 0.0)))
+    }
+
+    pub fn solve_parameter(&self, parameter: f64) -> Result<Box<dyn Function + 'a>, SolveError> {
+        let (birth_probability, death_probability) =
+            (self.birth_probability, self.death_probability);
+        let f = move |x: f64| {
             (birth_probability.get(x) * parameter - death_probability.get(x))
                 / (1.0 + death_probability.get(x))
         };
 
-        let step = width / (self.node_count - 1) as f64;
+        let u = self.node_values(&f)?;
+        let interpolant = self.interpolant(u, f, 1.0);
+        match self.tabulation {
+            Some((interpolation, point_count, extrapolation)) if self.width.is_finite() => Ok(
+                interpolation.tabulate(&interpolant, 0.0, self.width, point_count, extrapolation),
+            ),
+            _ => Ok(Box::new(interpolant)),
+        }
+    }
+
+    fn node_values(&self, f: &dyn Function) -> Result<Vec<f64>, SolveError> {
+        let f: Vec<f64> = self.nodes.par_iter().map(|&x| f.get(x)).collect();
+        self.solve_node_values(&f)
+    }
+
+    /// Like `solve_right_hand_side` with `f` given at the nodes, returns `u` at the nodes.
+    pub(crate) fn solve_node_values(&self, f: &[f64]) -> Result<Vec<f64>, SolveError> {
+        let b: Vec<f64> = f.iter().map(|f| -f).collect();
+        check_finite(&b, 1)?;
+
+        self.factorization.solve(&b)
+    }
+
+    pub(crate) fn nodes(&self) -> &[f64] {
+        &self.nodes
+    }
+
+    pub(crate) fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    fn interpolant<F>(&self, u: Vec<f64>, f: F, shift: f64) -> NystromInterpolant<'a, F> {
+        NystromInterpolant::new(
+            self.birth_probability,
+            self.death_probability,
+            self.nodes.clone(),
+            &self.weights,
+            &u,
+            f,
+            shift,
+        )
+    }
+}
+
+/// `shift + f(x) + Σ w_i k(x, y_i) u_i`, the natural extension of the node values
+/// to any `x`, which keeps the convergence order of the quadrature rule.
+pub(crate) struct NystromInterpolant<'a, F> {
+    birth_probability: &'a dyn Function,
+    death_probability: &'a dyn Function,
+    nodes: Vec<f64>,
+    weighted: Vec<f64>,
+    f: F,
+    shift: f64,
+}
+
+impl<'a, F> NystromInterpolant<'a, F> {
+    pub(crate) fn new(
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        nodes: Vec<f64>,
+        weights: &[f64],
+        u: &[f64],
+        f: F,
+        shift: f64,
+    ) -> Self {
+        Self {
+            birth_probability,
+            death_probability,
+            nodes,
+            weighted: u.iter().zip(weights).map(|(u, w)| u * w).collect(),
+            f,
+            shift,
+        }
+    }
+}
 
-        let mut mat: Vec<f64> = (0..self.node_count * self.node_count)
-            .map(|_| 0.0)
-            .collect();
+impl<F> Function for NystromInterpolant<'_, F>
+where
+    F: Fn(f64) -> f64 + Sync,
+{
+    fn get(&self, x: f64) -> f64 {
+        let integral: f64 = self
+            .nodes
+            .iter()
+            .zip(&self.weighted)
+            .map(|(&y, wu)| {
+                (self.birth_probability.get(y - x) + self.birth_probability.get(-y - x)) * wu
+            })
+            .sum();
 
-        for j in 0..self.node_count {
-            let x = (j as f64) * step;
-            for i in 0..self.node_count {
-                let y = (i as f64) * step;
+        self.shift + (self.f)(x) + integral / (1.0 + self.death_probability.get(x))
+    }
+}
+
+impl Method for NystromMethod {
+    fn solve<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<Box<dyn Function + 'a>, SolveError> {
+        self.prepare(birth_probability, death_probability, width)?
+            .solve_parameter(parameter)
+    }
+
+    fn solve_parameters<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameters: &[f64],
+        width: f64,
+    ) -> Result<Vec<Box<dyn Function + 'a>>, SolveError> {
+        let system = self.prepare(birth_probability, death_probability, width)?;
+        parameters
+            .iter()
+            .map(|&parameter| system.solve_parameter(parameter))
+            .collect()
+    }
 
-                let k = if i == self.node_count - 1 {
-                    (k(x, y) + k(x, -y)) / 2.0
-                } else if i == 0 {
-                    k(x, y) / 2.0
-                } else {
-                    k(x, y) + k(x, -y)
-                };
+    fn even_solution(&self) -> bool {
+        match self.tabulation {
+            Some((_, _, extrapolation)) => extrapolation.is_even(),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        integrals::TrapezoidIntegrator, krylov::GmresSolver, linear_equation::LUSolver,
+        problem::catalog,
+    };
+
+    #[test]
+    fn solve_parameters_matches_separate_solves() {
+        let method =
+            NystromMethod::new(Box::new(LUSolver), Box::new(TrapezoidIntegrator::new(101)));
+        let m = |x: f64| (-2.0 * x.abs()).exp();
+        let w = |x: f64| 0.5 * (-x.abs()).exp();
+        let parameters = [0.5, 1.0, 2.0];
 
-                mat[j * self.node_count + i] = if i == j { k * step - 1.0 } else { k * step };
+        let together = method.solve_parameters(&m, &w, &parameters, 10.0).unwrap();
+        for (solution, &parameter) in together.iter().zip(&parameters) {
+            let alone = method.solve(&m, &w, parameter, 10.0).unwrap();
+            for i in 0..=20 {
+                let x = i as f64 / 2.0;
+                assert!((solution.get(x) - alone.get(x)).abs() < 1e-12);
             }
         }
+    }
 
-        let b: Vec<f64> = (0..self.node_count)
-            .into_par_iter()
-            .map(|i| -f((i as f64) * step))
-            .collect();
+    #[test]
+    fn matrix_free_agrees_with_dense() {
+        let dense = NystromMethod::new(Box::new(LUSolver), Box::new(TrapezoidIntegrator::new(201)));
+        let matrix_free = NystromMethod::matrix_free(
+            Box::new(GmresSolver::new(1e-13, 50, 1000)),
+            Box::new(TrapezoidIntegrator::new(201)),
+        );
 
-        Box::new(PointFunction::new(
-            self.solver
-                .solve(&mat, self.node_count, &b)
-                .unwrap()
-                .iter()
-                .map(|x| x + 1.0)
-                .collect(),
-            0.0,
-            width,
-        ))
+        for problem in catalog() {
+            let solve = |method: &NystromMethod| {
+                let solution = method
+                    .solve(
+                        problem.birth_probability.as_ref(),
+                        problem.death_probability.as_ref(),
+                        problem.parameter,
+                        problem.width,
+                    )
+                    .unwrap();
+                (0..=50)
+                    .map(|i| solution.get(problem.width * i as f64 / 50.0))
+                    .collect::<Vec<f64>>()
+            };
+            for (d, m) in solve(&dense).iter().zip(solve(&matrix_free)) {
+                assert!((d - m).abs() < 1e-12, "{}: {d} != {m}", problem.name);
+            }
+        }
     }
 }
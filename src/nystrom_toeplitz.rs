@@ -0,0 +1,186 @@
+use rayon::prelude::*;
+
+use crate::{
+    convolution::ConvolutionOperator,
+    functions::*,
+    interpolation::{Extrapolation, PointFunction},
+    krylov::IterativeSolver,
+    method::{check_finite, Diagnostics, Method, SolveError},
+};
+
+/// Nyström method on the same uniform grid as `NystromMethod`, but the kernel is applied
+/// by a `ConvolutionOperator`, so one Krylov iteration costs `O(n log n)` instead of a
+/// dense `O(n³)` factorization.
+pub struct ToeplitzNystromMethod {
+    solver: Box<dyn IterativeSolver>,
+    node_count: usize,
+}
+
+impl ToeplitzNystromMethod {
+    pub fn new(solver: Box<dyn IterativeSolver>, node_count: usize) -> Self {
+        Self { solver, node_count }
+    }
+
+    /// Solves the shifted equation for `u = c - 1` and returns its values on the grid.
+    fn node_values(
+        &self,
+        birth_probability: &dyn Function,
+        death_probability: &dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<(Vec<f64>, Diagnostics), SolveError> {
+        if !width.is_finite() {
+            return Err(SolveError::UnboundedDomain);
+        }
+        let kernel =
+            ConvolutionOperator::new(birth_probability, death_probability, self.node_count, width)?;
+        let step = width / (self.node_count - 1) as f64;
+
+        let f: Vec<f64> = (0..self.node_count)
+            .into_par_iter()
+            .map(|i| {
+                let x = (i as f64) * step;
+                (birth_probability.get(x) * parameter - death_probability.get(x))
+                    / (1.0 + death_probability.get(x))
+            })
+            .collect();
+        check_finite(&f, 1)?;
+
+        let operator = |u: &[f64]| {
+            kernel
+                .apply(u)
+                .iter()
+                .zip(u)
+                .map(|(ku, u)| u - ku)
+                .collect()
+        };
+
+        let solution = self.solver.iterate(&operator, &f)?;
+        let iterations = solution.residual_history.len();
+        let diagnostics = Diagnostics {
+            iterations: Some(iterations),
+            warnings: match (solution.converged, solution.residual_history.last()) {
+                (false, Some(residual)) => vec![format!(
+                    "tolerance not met after {iterations} iterations, relative residual {residual}"
+                )],
+                (false, None) => {
+                    vec!["the solver broke down before its first iteration".to_string()]
+                }
+                (true, _) => vec![],
+            },
+            ..Default::default()
+        };
+        Ok((solution.x, diagnostics))
+    }
+}
+
+impl Method for ToeplitzNystromMethod {
+    fn solve<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<Box<dyn Function + 'a>, SolveError> {
+        self.solve_with_diagnostics(birth_probability, death_probability, parameter, width)
+            .map(|(solution, _)| solution)
+    }
+
+    fn solve_with_diagnostics<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<(Box<dyn Function + 'a>, Diagnostics), SolveError> {
+        let (u, diagnostics) =
+            self.node_values(birth_probability, death_probability, parameter, width)?;
+
+        Ok((
+            Box::new(PointFunction::new(
+                u.iter().map(|x| x + 1.0).collect(),
+                0.0,
+                width,
+                Extrapolation::EvenReflection,
+            )),
+            diagnostics,
+        ))
+    }
+
+    fn even_solution(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        integrals::TrapezoidIntegrator, krylov::GmresSolver, linear_equation::LUSolver,
+        nystrom::NystromMethod,
+    };
+    use std::f64::consts::FRAC_1_PI;
+
+    /// Kernel, death term and parameter of a problem.
+    type Problem = (Box<dyn Function>, Box<dyn Function>, f64);
+
+    /// The exponent and rational problems of `main.rs`.
+    fn problems() -> Vec<Problem> {
+        let q = |x: f64| x * x / 3.0 - 16.0 / 9.0 * x.abs() + 56.0 / 27.0 + 1.0 / 3.0;
+        let exponent: Problem = (
+            Box::new(|x: f64| (-2.0 * x.abs()).exp()),
+            Box::new(move |x: f64| {
+                (-x.abs()).exp() * q(x) / (1.0 + (-x.abs()).exp() * (x * x + 1.0))
+            }),
+            2.0 / 3.0 + 52.0 / 27.0,
+        );
+        let rational: Problem = (
+            Box::new(|x: f64| FRAC_1_PI / (x * x + 1.0)),
+            Box::new(|x: f64| 1.0 / (x * x + 9.0)),
+            std::f64::consts::PI * 6.0 * 9.0 / 142.0,
+        );
+        vec![exponent, rational]
+    }
+
+    #[test]
+    fn agrees_with_dense_nystrom() {
+        let (n, width) = (301, 15.0);
+        let step = width / (n - 1) as f64;
+        let structured = ToeplitzNystromMethod::new(Box::new(GmresSolver::new(1e-13, 50, 1000)), n);
+        let dense = NystromMethod::new(Box::new(LUSolver), Box::new(TrapezoidIntegrator::new(n)));
+
+        for (m, w, parameter) in problems() {
+            let (u, diagnostics) = structured
+                .node_values(m.as_ref(), w.as_ref(), parameter, width)
+                .unwrap();
+            assert!(
+                diagnostics.warnings.is_empty(),
+                "{:?}",
+                diagnostics.warnings
+            );
+            let c = dense
+                .solve(m.as_ref(), w.as_ref(), parameter, width)
+                .unwrap();
+            for (i, u) in u.iter().enumerate() {
+                let d = c.get(i as f64 * step);
+                assert!((u + 1.0 - d).abs() < 1e-10, "{} != {d}", u + 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn unmet_tolerance_returns_the_last_iterate() {
+        let method = ToeplitzNystromMethod::new(Box::new(GmresSolver::new(1e-15, 2, 2)), 101);
+        for (m, w, parameter) in problems() {
+            let (solution, diagnostics) = method
+                .solve_with_diagnostics(m.as_ref(), w.as_ref(), parameter, 15.0)
+                .unwrap();
+            assert_eq!(diagnostics.iterations, Some(2));
+            assert!(diagnostics
+                .warnings
+                .iter()
+                .any(|warning| warning.contains("not met")));
+            assert!(solution.get(1.0).is_finite());
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use std::{collections::BTreeMap, fmt, path::Path};
+
+use serde::Deserialize;
+
+use crate::{
+    expression::{Expression, ExpressionError, ExpressionFunction},
+    functions::Function,
+};
+
+/// The only symmetry a problem file may declare. Every method folds the kernel by evenness,
+/// so other problems cannot be solved yet and fail to parse.
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Symmetry {
+    Even,
+}
+
+#[derive(Debug)]
+pub enum ProblemError {
+    /// The file could not be read.
+    Io(std::io::Error),
+    /// The file is not valid TOML or misses a field.
+    Format(toml::de::Error),
+    /// An expression in the given field does not parse.
+    Expression {
+        field: &'static str,
+        error: ExpressionError,
+    },
+    /// `width` is not positive and finite.
+    InvalidWidth { width: f64 },
+    /// The problem is declared even, but the given field is not.
+    NotEven { field: &'static str, x: f64 },
+}
+
+impl fmt::Display for ProblemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProblemError::Io(error) => write!(f, "cannot read problem: {error}"),
+            ProblemError::Format(error) => write!(f, "invalid problem: {error}"),
+            ProblemError::Expression { field, error } => write!(f, "in '{field}': {error}"),
+            ProblemError::InvalidWidth { width } => {
+                write!(f, "width must be positive and finite, got {width}")
+            }
+            ProblemError::NotEven { field, x } => {
+                write!(f, "'{field}' is declared even but differs at {x} and -{x}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProblemError {}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Value {
+    Number(f64),
+    Expression(String),
+}
+
+/// The layout of a problem file, all expressions may use the `constants`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProblemFile {
+    name: String,
+    width: f64,
+    #[serde(rename = "symmetry", default)]
+    _symmetry: Option<Symmetry>,
+    birth: String,
+    death: String,
+    parameter: Value,
+    solution: Option<String>,
+    #[serde(default)]
+    constants: BTreeMap<String, f64>,
+}
+
+/// An instance of the twin equation with birth kernel `m` and death term `w`, and its
+/// exact solution where one is known.
+pub struct Problem {
+    pub name: String,
+    pub birth_probability: Box<dyn Function>,
+    pub death_probability: Box<dyn Function>,
+    pub parameter: f64,
+    pub solution: Option<Box<dyn Function>>,
+    pub width: f64,
+}
+
+const EVENNESS_POINT_COUNT: usize = 50;
+
+impl Problem {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ProblemError> {
+        Self::parse(&std::fs::read_to_string(path).map_err(ProblemError::Io)?)
+    }
+
+    /// Parses a problem in TOML, see `problems/` for examples.
+    pub fn parse(source: &str) -> Result<Self, ProblemError> {
+        let file: ProblemFile = toml::from_str(source).map_err(ProblemError::Format)?;
+        if !(file.width > 0.0 && file.width.is_finite()) {
+            return Err(ProblemError::InvalidWidth { width: file.width });
+        }
+
+        let constants: Vec<(&str, f64)> = file
+            .constants
+            .iter()
+            .map(|(name, &value)| (name.as_str(), value))
+            .collect();
+        let function = |field: &'static str, source: &str| {
+            ExpressionFunction::parse(source, &constants)
+                .map_err(|error| ProblemError::Expression { field, error })
+        };
+
+        let parameter = match &file.parameter {
+            Value::Number(number) => *number,
+            Value::Expression(source) => Expression::parse(source, &[], &constants)
+                .map_err(|error| ProblemError::Expression {
+                    field: "parameter",
+                    error,
+                })?
+                .evaluate(&[]),
+        };
+        let problem = Self {
+            birth_probability: Box::new(function("birth", &file.birth)?),
+            death_probability: Box::new(function("death", &file.death)?),
+            solution: match &file.solution {
+                Some(source) => Some(Box::new(function("solution", source)?)),
+                None => None,
+            },
+            name: file.name,
+            parameter,
+            width: file.width,
+        };
+
+        problem.check_even()?;
+        Ok(problem)
+    }
+
+    /// Samples the functions on `(0, width]`, which catches a forgotten `abs`.
+    fn check_even(&self) -> Result<(), ProblemError> {
+        let mut fields: Vec<(&'static str, &dyn Function)> = vec![
+            ("birth", self.birth_probability.as_ref()),
+            ("death", self.death_probability.as_ref()),
+        ];
+        if let Some(solution) = &self.solution {
+            fields.push(("solution", solution.as_ref()));
+        }
+
+        for (field, function) in fields {
+            for i in 1..=EVENNESS_POINT_COUNT {
+                let x = self.width * i as f64 / EVENNESS_POINT_COUNT as f64;
+                let (right, left) = (function.get(x), function.get(-x));
+                if (right - left).abs() > 1e-12 * right.abs().max(1.0) {
+                    return Err(ProblemError::NotEven { field, x });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+const CATALOG: [&str; 2] = [
+    include_str!("../problems/exponent.toml"),
+    include_str!("../problems/rational.toml"),
+];
+
+/// The test problems that ship with the crate, the files in `problems/`.
+pub fn catalog() -> Vec<Problem> {
+    CATALOG
+        .iter()
+        .map(|source| Problem::parse(source).expect("built-in problems are valid"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINIMAL: &str = r#"
+name = "minimal"
+width = 10.0
+birth = "a * exp(-abs(x))"
+death = "0.5"
+parameter = "2 * a"
+[constants]
+a = 0.25
+"#;
+
+    #[test]
+    fn parses_a_problem() {
+        let problem = Problem::parse(MINIMAL).unwrap();
+        assert_eq!(problem.name, "minimal");
+        assert_eq!(problem.parameter, 0.5);
+        assert_eq!(problem.birth_probability.get(0.0), 0.25);
+        assert_eq!(problem.death_probability.get(3.0), 0.5);
+        assert!(problem.solution.is_none());
+    }
+
+    #[test]
+    fn reports_invalid_problems() {
+        let missing = MINIMAL.replace("death = \"0.5\"\n", "");
+        assert!(matches!(
+            Problem::parse(&missing),
+            Err(ProblemError::Format(_))
+        ));
+
+        let bad = MINIMAL.replace("\"0.5\"", "\"0.5 +\"");
+        assert!(matches!(
+            Problem::parse(&bad),
+            Err(ProblemError::Expression {
+                field: "death",
+                error: ExpressionError::UnexpectedEnd
+            })
+        ));
+
+        let odd = MINIMAL.replace("exp(-abs(x))", "exp(-x)");
+        assert!(matches!(
+            Problem::parse(&odd),
+            Err(ProblemError::NotEven { field: "birth", .. })
+        ));
+
+        let infinite = MINIMAL.replace("10.0", "inf");
+        assert!(matches!(
+            Problem::parse(&infinite),
+            Err(ProblemError::InvalidWidth { .. })
+        ));
+
+        let asymmetric = MINIMAL.replace("width = 10.0", "width = 10.0\nsymmetry = \"none\"");
+        assert!(matches!(
+            Problem::parse(&asymmetric),
+            Err(ProblemError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn catalog_loads() {
+        let problems = catalog();
+        let names: Vec<&str> = problems
+            .iter()
+            .map(|problem| problem.name.as_str())
+            .collect();
+        assert_eq!(names, ["exponent", "rational"]);
+
+        // c(x) = 1 + e^{-|x|} (x^2 + 1) for the exponent problem.
+        let exponent = problems[0].solution.as_deref().unwrap();
+        assert!((exponent.get(0.0) - 2.0).abs() < 1e-15);
+        assert!((exponent.get(-1.0) - (1.0 + 2.0 * (-1.0f64).exp())).abs() < 1e-15);
+    }
+}
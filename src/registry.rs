@@ -0,0 +1,384 @@
+use std::fmt;
+
+use crate::{
+    basis::{
+        BSplineBasis, CosineBasis, EvenPowerBasis, OrthogonalPolynomialBasis, PolynomialFamily,
+    },
+    collocation::{CollocationMethod, CollocationPoints},
+    functions::Function,
+    galerkin::Galerkin,
+    integrals::{GaussLegendreIntegrator, GradedMeshIntegrator, TrapezoidIntegrator},
+    interpolation::{Extrapolation, Interpolation},
+    krylov::GmresSolver,
+    linear_equation::{LUSolver, PivotedLUSolver, Pivoting},
+    method::Method,
+    neumann::{Acceleration, NeumannMethod},
+    nystrom::NystromMethod,
+    nystrom_toeplitz::ToeplitzNystromMethod,
+    sloan::{SloanEvaluation, SloanIteration},
+    two_grid::TwoGridMethod,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingError {
+    /// No method of that name is registered.
+    UnknownMethod { method: String },
+    /// The method has no parameter of that name.
+    UnknownParameter { method: String, parameter: String },
+    /// A parameter that counts something was set to a value that is not a positive integer.
+    NotACount { parameter: String, value: f64 },
+    /// A parameter that has to be a positive real, like a grading or a tolerance, was not.
+    NotPositive { parameter: String, value: f64 },
+}
+
+impl fmt::Display for SettingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingError::UnknownMethod { method } => write!(f, "unknown method '{method}'"),
+            SettingError::UnknownParameter { method, parameter } => {
+                write!(f, "method '{method}' has no parameter '{parameter}'")
+            }
+            SettingError::NotACount { parameter, value } => {
+                write!(f, "'{parameter}' must be a positive integer, got {value}")
+            }
+            SettingError::NotPositive { parameter, value } => {
+                write!(f, "'{parameter}' must be a positive number, got {value}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SettingError {}
+
+/// A method that can be built by name, with the resolution parameters it takes and their
+/// defaults.
+pub struct MethodEntry {
+    pub name: &'static str,
+    pub parameters: &'static [(&'static str, f64)],
+    builder: fn(&Settings) -> Result<Box<dyn Method>, SettingError>,
+}
+
+pub const METHODS: [MethodEntry; 12] = [
+    MethodEntry {
+        name: "galerkin_taylor",
+        parameters: &[("degree", 60.0), ("intervals", 40.0)],
+        builder: |s| {
+            Ok(Box::new(Galerkin::new(
+                Box::new(GaussLegendreIntegrator::composite(8, s.count("intervals")?)),
+                Box::new(LUSolver),
+                projection_weight(),
+                EvenPowerBasis::new(s.count("degree")?),
+            )))
+        },
+    },
+    MethodEntry {
+        name: "galerkin_fourier",
+        parameters: &[("degree", 400.0), ("nodes", 500.0)],
+        builder: |s| {
+            Ok(Box::new(Galerkin::new(
+                Box::new(TrapezoidIntegrator::new(s.count("nodes")?)),
+                Box::new(LUSolver),
+                projection_weight(),
+                CosineBasis::new(s.count("degree")?),
+            )))
+        },
+    },
+    MethodEntry {
+        name: "galerkin_legendre",
+        parameters: &[("degree", 60.0), ("intervals", 40.0)],
+        builder: |s| {
+            Ok(Box::new(Galerkin::new(
+                Box::new(GaussLegendreIntegrator::composite(8, s.count("intervals")?)),
+                pivoted(),
+                projection_weight(),
+                OrthogonalPolynomialBasis::new(s.count("degree")?, PolynomialFamily::Legendre),
+            )))
+        },
+    },
+    MethodEntry {
+        name: "galerkin_legendre_sloan",
+        parameters: &[
+            ("degree", 60.0),
+            ("intervals", 40.0),
+            ("iterations", 1.0),
+            ("sloan_intervals", 60.0),
+        ],
+        builder: |s| {
+            Ok(Box::new(SloanIteration::new(
+                Galerkin::new(
+                    Box::new(GaussLegendreIntegrator::composite(8, s.count("intervals")?)),
+                    pivoted(),
+                    projection_weight(),
+                    OrthogonalPolynomialBasis::new(s.count("degree")?, PolynomialFamily::Legendre),
+                ),
+                Box::new(GaussLegendreIntegrator::composite(
+                    8,
+                    s.count("sloan_intervals")?,
+                )),
+                s.count("iterations")?,
+                SloanEvaluation::Lazy,
+            )))
+        },
+    },
+    MethodEntry {
+        name: "galerkin_spline",
+        parameters: &[("elements", 40.0), ("grading", 2.0), ("intervals", 160.0)],
+        builder: |s| {
+            Ok(Box::new(Galerkin::unweighted(
+                Box::new(GradedMeshIntegrator::new(
+                    4,
+                    s.count("intervals")?,
+                    s.positive("grading")?,
+                )),
+                pivoted(),
+                BSplineBasis::cubic(s.count("elements")?, s.positive("grading")?),
+            )))
+        },
+    },
+    MethodEntry {
+        name: "collocation_legendre",
+        parameters: &[("degree", 60.0), ("intervals", 40.0)],
+        builder: |s| {
+            Ok(Box::new(CollocationMethod::new(
+                Box::new(GaussLegendreIntegrator::composite(8, s.count("intervals")?)),
+                pivoted(),
+                OrthogonalPolynomialBasis::new(s.count("degree")?, PolynomialFamily::Legendre),
+                CollocationPoints::Chebyshev,
+            )))
+        },
+    },
+    MethodEntry {
+        name: "collocation_fourier",
+        parameters: &[("degree", 400.0), ("nodes", 500.0)],
+        builder: |s| {
+            Ok(Box::new(CollocationMethod::new(
+                Box::new(TrapezoidIntegrator::new(s.count("nodes")?)),
+                pivoted(),
+                CosineBasis::new(s.count("degree")?),
+                CollocationPoints::Uniform,
+            )))
+        },
+    },
+    MethodEntry {
+        name: "collocation_hat",
+        parameters: &[("elements", 160.0), ("grading", 2.0), ("intervals", 640.0)],
+        builder: |s| {
+            Ok(Box::new(CollocationMethod::new(
+                Box::new(GradedMeshIntegrator::new(
+                    4,
+                    s.count("intervals")?,
+                    s.positive("grading")?,
+                )),
+                pivoted(),
+                BSplineBasis::hat(s.count("elements")?, s.positive("grading")?),
+                CollocationPoints::Graded(s.positive("grading")?),
+            )))
+        },
+    },
+    MethodEntry {
+        name: "neumann",
+        parameters: &[
+            ("nodes", 5000.0),
+            ("max_iterations", 500.0),
+            ("tolerance", 1e-10),
+            ("depth", 5.0),
+        ],
+        builder: |s| {
+            Ok(Box::new(NeumannMethod::convolution(
+                s.count("max_iterations")?,
+                s.positive("tolerance")?,
+                s.count("nodes")?,
+                Acceleration::Anderson {
+                    depth: s.count("depth")?,
+                },
+            )))
+        },
+    },
+    MethodEntry {
+        name: "nystrom",
+        parameters: &[("nodes", 5000.0)],
+        builder: |s| {
+            Ok(Box::new(
+                NystromMethod::new(
+                    pivoted(),
+                    Box::new(TrapezoidIntegrator::new(s.count("nodes")?)),
+                )
+                .with_interpolation(
+                    Interpolation::NaturalSpline,
+                    s.count("nodes")?,
+                    Extrapolation::DecayToOne,
+                ),
+            ))
+        },
+    },
+    MethodEntry {
+        name: "two_grid",
+        parameters: &[
+            ("coarse_nodes", 500.0),
+            ("fine_nodes", 5000.0),
+            ("tolerance", 1e-10),
+            ("max_iterations", 50.0),
+        ],
+        builder: |s| {
+            Ok(Box::new(TwoGridMethod::convolution(
+                NystromMethod::new(
+                    pivoted(),
+                    Box::new(TrapezoidIntegrator::new(s.count("coarse_nodes")?)),
+                ),
+                s.count("fine_nodes")?,
+                s.positive("tolerance")?,
+                s.count("max_iterations")?,
+            )))
+        },
+    },
+    MethodEntry {
+        name: "nystrom_toeplitz",
+        parameters: &[
+            ("nodes", 5000.0),
+            ("tolerance", 1e-12),
+            ("restart", 30.0),
+            ("max_iterations", 1000.0),
+        ],
+        builder: |s| {
+            Ok(Box::new(ToeplitzNystromMethod::new(
+                Box::new(GmresSolver::new(
+                    s.positive("tolerance")?,
+                    s.count("restart")?,
+                    s.count("max_iterations")?,
+                )),
+                s.count("nodes")?,
+            )))
+        },
+    },
+];
+
+/// The entry of the method called `name`.
+pub fn method_entry(name: &str) -> Result<&'static MethodEntry, SettingError> {
+    METHODS
+        .iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| SettingError::UnknownMethod {
+            method: name.to_string(),
+        })
+}
+
+/// The parameters of one method after overrides.
+struct Settings {
+    values: Vec<(&'static str, f64)>,
+}
+
+impl Settings {
+    fn get(&self, name: &str) -> f64 {
+        self.values
+            .iter()
+            .find(|&&(parameter, _)| parameter == name)
+            .map(|&(_, value)| value)
+            .expect("parameter is registered")
+    }
+
+    fn positive(&self, name: &str) -> Result<f64, SettingError> {
+        let value = self.get(name);
+        if value > 0.0 && value.is_finite() {
+            Ok(value)
+        } else {
+            Err(SettingError::NotPositive {
+                parameter: name.to_string(),
+                value,
+            })
+        }
+    }
+
+    fn count(&self, name: &str) -> Result<usize, SettingError> {
+        let value = self.get(name);
+        if value >= 1.0 && value.fract() == 0.0 {
+            Ok(value as usize)
+        } else {
+            Err(SettingError::NotACount {
+                parameter: name.to_string(),
+                value,
+            })
+        }
+    }
+}
+
+fn pivoted() -> Box<PivotedLUSolver> {
+    Box::new(PivotedLUSolver::new(Pivoting::Partial))
+}
+
+fn projection_weight() -> Box<dyn Function> {
+    Box::new(|t: f64| (1.0f64 - t.powi(2)).sqrt())
+}
+
+impl MethodEntry {
+    pub fn has_parameter(&self, name: &str) -> bool {
+        self.parameters
+            .iter()
+            .any(|&(parameter, _)| parameter == name)
+    }
+
+    /// Builds the method from its defaults with `overrides` applied on top.
+    pub fn build(&self, overrides: &[(String, f64)]) -> Result<Box<dyn Method>, SettingError> {
+        let mut settings = Settings {
+            values: self.parameters.to_vec(),
+        };
+        for (name, value) in overrides {
+            match settings
+                .values
+                .iter_mut()
+                .find(|(parameter, _)| parameter == name)
+            {
+                Some((_, current)) => *current = *value,
+                None => {
+                    return Err(SettingError::UnknownParameter {
+                        method: self.name.to_string(),
+                        parameter: name.clone(),
+                    })
+                }
+            }
+        }
+        (self.builder)(&settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str) -> &'static MethodEntry {
+        METHODS.iter().find(|entry| entry.name == name).unwrap()
+    }
+
+    #[test]
+    fn builds_every_method_from_defaults() {
+        for entry in &METHODS {
+            assert!(entry.build(&[]).is_ok(), "{}", entry.name);
+        }
+    }
+
+    #[test]
+    fn rejects_non_positive_reals() {
+        for (name, parameter, value) in [
+            ("galerkin_spline", "grading", 0.0),
+            ("collocation_hat", "grading", -2.0),
+            ("neumann", "tolerance", f64::NAN),
+            ("two_grid", "tolerance", -1e-10),
+        ] {
+            let error = entry(name)
+                .build(&[(parameter.to_string(), value)])
+                .err()
+                .unwrap();
+            assert!(
+                matches!(error, SettingError::NotPositive { parameter: ref p, .. } if p == parameter)
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_parameters() {
+        let error = entry("nystrom")
+            .build(&[("grading".to_string(), 2.0)])
+            .err()
+            .unwrap();
+        assert!(matches!(error, SettingError::UnknownParameter { .. }));
+    }
+}
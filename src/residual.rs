@@ -0,0 +1,100 @@
+use rayon::prelude::*;
+
+use crate::{functions::Function, integrals::Integrator};
+
+pub struct ResidualNorms {
+    pub max: f64,
+    pub l2: f64,
+}
+
+/// Twin-equation residual of `c` at `x`, written for `u = c - 1` as the methods solve it:
+/// `(1 + w(x)) u(x) - ∫m(t - x) u(t) dt - (m(x) parameter - w(x))`.
+/// The integral is taken over `[-width, width]`, folded onto `[0, width]`
+/// the same way the methods assume `c` to be even, and split at `x` where `m(t - x)`
+/// has its kink, so that a rule with no node there keeps its order.
+pub fn residual(
+    solution: &dyn Function,
+    birth_probability: &dyn Function,
+    death_probability: &dyn Function,
+    parameter: f64,
+    width: f64,
+    integrator: &dyn Integrator,
+    x: f64,
+) -> f64 {
+    let u = |t: f64| solution.get(t) - 1.0;
+    let integrand = |t: f64| (birth_probability.get(t - x) + birth_probability.get(-t - x)) * u(t);
+    let integral = if 0.0 < x && x < width {
+        integrator.integrate(&integrand, 0.0, x) + integrator.integrate(&integrand, x, width)
+    } else {
+        integrator.integrate(&integrand, 0.0, width)
+    };
+
+    (1.0 + death_probability.get(x)) * u(x)
+        - integral
+        - (birth_probability.get(x) * parameter - death_probability.get(x))
+}
+
+/// L∞ and L2 norms of the residual over the given ascending points,
+/// the L2 norm uses the trapezoid rule between them.
+pub fn residual_norms(
+    solution: &dyn Function,
+    birth_probability: &dyn Function,
+    death_probability: &dyn Function,
+    parameter: f64,
+    width: f64,
+    integrator: &dyn Integrator,
+    points: &[f64],
+) -> ResidualNorms {
+    let values: Vec<f64> = points
+        .par_iter()
+        .map(|&x| {
+            residual(
+                solution,
+                birth_probability,
+                death_probability,
+                parameter,
+                width,
+                integrator,
+                x,
+            )
+        })
+        .collect();
+
+    let max = values.iter().fold(0.0f64, |acc, r| acc.max(r.abs()));
+    let l2 = points
+        .windows(2)
+        .zip(values.windows(2))
+        .map(|(x, r)| (x[1] - x[0]) * (r[0] * r[0] + r[1] * r[1]) / 2.0)
+        .sum::<f64>()
+        .sqrt();
+
+    ResidualNorms { max, l2 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::integrals::GaussLegendreIntegrator;
+
+    #[test]
+    fn vanishes_on_the_exact_solution_only() {
+        let q = |x: f64| x * x / 3.0 - 16.0 / 9.0 * x.abs() + 56.0 / 27.0 + 1.0 / 3.0;
+        let m = |x: f64| (-2.0 * x.abs()).exp();
+        let w = move |x: f64| (-x.abs()).exp() * q(x) / (1.0 + (-x.abs()).exp() * (x * x + 1.0));
+        let c = |x: f64| 1.0 + (-x.abs()).exp() * (x * x + 1.0);
+        let shifted = |x: f64| c(x) + 0.01;
+        let parameter = 2.0 / 3.0 + 52.0 / 27.0;
+
+        // Few nodes suffice, since the integral is split at the kink of the kernel.
+        let integrator = GaussLegendreIntegrator::composite(8, 16);
+        let points: Vec<f64> = (0..=50).map(|i| i as f64 / 10.0).collect();
+
+        let exact = residual_norms(&c, &m, &w, parameter, 15.0, &integrator, &points);
+        assert!(exact.max < 1e-12, "{}", exact.max);
+        assert!(exact.l2 < 1e-12, "{}", exact.l2);
+
+        // A constant shift `δ` leaves `δ w(x)`, since the kernel integrates to one.
+        let wrong = residual_norms(&shifted, &m, &w, parameter, 15.0, &integrator, &points);
+        assert!((wrong.max - 0.01 * w(0.0)).abs() < 1e-12, "{}", wrong.max);
+    }
+}
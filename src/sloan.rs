@@ -0,0 +1,222 @@
+use crate::{
+    functions::Function,
+    integrals::Integrator,
+    interpolation::{Extrapolation, PointFunction},
+    method::{Method, SolveError},
+};
+
+use rayon::prelude::*;
+
+/// How the iterates are represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SloanEvaluation {
+    /// Every evaluation integrates the previous iterate, so `k` iterations cost
+    /// `k` nested integrals per point.
+    Lazy,
+    /// Every iterate is tabulated on this many points of `[0, width]`, at least 2, and
+    /// interpolated.
+    Grid(usize),
+}
+
+/// Post-processes the solution `c` of another method by `iterations` Sloan iterations
+/// `c(x) <- 1 + f(x) + ∫k(x, t) (c(t) - 1) dt`, which for a projection method gains
+/// the accuracy of the quadrature instead of that of the projection.
+pub struct SloanIteration<M: Method> {
+    method: M,
+    integrator: Box<dyn Integrator>,
+    iterations: usize,
+    evaluation: SloanEvaluation,
+}
+
+impl<M: Method> SloanIteration<M> {
+    pub fn new(
+        method: M,
+        integrator: Box<dyn Integrator>,
+        iterations: usize,
+        evaluation: SloanEvaluation,
+    ) -> Self {
+        if let SloanEvaluation::Grid(node_count) = evaluation {
+            assert!(node_count >= 2, "a Sloan grid needs at least 2 points");
+        }
+        Self {
+            method,
+            integrator,
+            iterations,
+            evaluation,
+        }
+    }
+}
+
+struct SloanIterate<'a> {
+    previous: Box<dyn Function + 'a>,
+    birth_probability: &'a dyn Function,
+    death_probability: &'a dyn Function,
+    integrator: &'a dyn Integrator,
+    parameter: f64,
+    width: f64,
+}
+
+impl Function for SloanIterate<'_> {
+    fn get(&self, x: f64) -> f64 {
+        let birth_probability = self.birth_probability;
+        let integrand = |t: f64| {
+            (birth_probability.get(t - x) + birth_probability.get(-t - x))
+                * (self.previous.get(t) - 1.0)
+        };
+        // The kernel usually has a kink where `t = |x|`, each piece is smooth.
+        let split = x.abs().min(self.width);
+        let integral = self.integrator.integrate(&integrand, 0.0, split)
+            + self.integrator.integrate(&integrand, split, self.width);
+
+        1.0 + (birth_probability.get(x) * self.parameter - self.death_probability.get(x) + integral)
+            / (1.0 + self.death_probability.get(x))
+    }
+}
+
+impl<M: Method> Method for SloanIteration<M> {
+    fn solve<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<Box<dyn Function + 'a>, SolveError> {
+        if !width.is_finite() {
+            return Err(SolveError::UnboundedDomain);
+        }
+        let mut solution =
+            self.method
+                .solve(birth_probability, death_probability, parameter, width)?;
+
+        for _ in 0..self.iterations {
+            let iterate = SloanIterate {
+                previous: solution,
+                birth_probability,
+                death_probability,
+                integrator: self.integrator.as_ref(),
+                parameter,
+                width,
+            };
+
+            solution = match self.evaluation {
+                SloanEvaluation::Lazy => Box::new(iterate),
+                SloanEvaluation::Grid(node_count) => {
+                    let step = width / (node_count - 1) as f64;
+                    let points = (0..node_count)
+                        .into_par_iter()
+                        .map(|i| iterate.get(i as f64 * step))
+                        .collect();
+                    Box::new(PointFunction::new(
+                        points,
+                        0.0,
+                        width,
+                        Extrapolation::EvenReflection,
+                    ))
+                }
+            };
+        }
+
+        Ok(solution)
+    }
+
+    fn even_solution(&self) -> bool {
+        self.iterations > 0 || self.method.even_solution()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        basis::{OrthogonalPolynomialBasis, PolynomialFamily},
+        galerkin::Galerkin,
+        integrals::{GaussLegendreIntegrator, TrapezoidIntegrator},
+        interpolation::Interpolation,
+        linear_equation::LUSolver,
+        nystrom::NystromMethod,
+        problem::catalog,
+    };
+
+    fn galerkin() -> Galerkin<OrthogonalPolynomialBasis> {
+        Galerkin::new(
+            Box::new(GaussLegendreIntegrator::composite(8, 40)),
+            Box::new(LUSolver),
+            Box::new(|t: f64| (1.0f64 - t.powi(2)).sqrt()),
+            OrthogonalPolynomialBasis::new(20, PolynomialFamily::Legendre),
+        )
+    }
+
+    fn sloan(iterations: usize) -> SloanIteration<Galerkin<OrthogonalPolynomialBasis>> {
+        SloanIteration::new(
+            galerkin(),
+            Box::new(GaussLegendreIntegrator::composite(8, 40)),
+            iterations,
+            SloanEvaluation::Lazy,
+        )
+    }
+
+    #[test]
+    fn evenness_follows_the_method_without_iterations() {
+        let clamped = || {
+            NystromMethod::new(Box::new(LUSolver), Box::new(TrapezoidIntegrator::new(11)))
+                .with_interpolation(Interpolation::Linear, 11, Extrapolation::Clamp)
+        };
+        let sloan = |iterations| {
+            SloanIteration::new(
+                clamped(),
+                Box::new(TrapezoidIntegrator::new(11)),
+                iterations,
+                SloanEvaluation::Lazy,
+            )
+        };
+        assert!(!sloan(0).even_solution());
+        assert!(sloan(1).even_solution());
+    }
+
+    #[test]
+    fn one_iteration_reduces_the_error() {
+        let problem = catalog().remove(0);
+        let actual = problem.solution.as_deref().unwrap();
+        let max_error = |method: &dyn Method| {
+            let solution = method
+                .solve(
+                    problem.birth_probability.as_ref(),
+                    problem.death_probability.as_ref(),
+                    problem.parameter,
+                    problem.width,
+                )
+                .unwrap();
+            (0..=30)
+                .map(|i| problem.width * i as f64 / 30.0)
+                .map(|x| (solution.get(x) - actual.get(x)).abs())
+                .fold(0.0, f64::max)
+        };
+
+        let (projected, iterated) = (max_error(&galerkin()), max_error(&sloan(1)));
+        assert!(iterated < projected / 5.0, "{iterated} vs {projected}");
+    }
+
+    #[test]
+    #[should_panic(expected = "at least 2 points")]
+    fn grid_needs_two_points() {
+        SloanIteration::new(
+            galerkin(),
+            Box::new(TrapezoidIntegrator::new(11)),
+            1,
+            SloanEvaluation::Grid(1),
+        );
+    }
+
+    #[test]
+    fn infinite_width_is_an_error() {
+        let sloan = SloanIteration::new(
+            galerkin(),
+            Box::new(TrapezoidIntegrator::new(11)),
+            1,
+            SloanEvaluation::Grid(11),
+        );
+        let m = |x: f64| (-2.0 * x.abs()).exp();
+        let result = sloan.solve(&m, &m, 1.0, f64::INFINITY);
+        assert_eq!(result.err(), Some(SolveError::UnboundedDomain));
+    }
+}
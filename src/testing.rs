@@ -1,6 +1,13 @@
-use crate::{functions::Function, method::Method};
+use crate::{
+    functions::Function,
+    integrals::{GaussLegendreIntegrator, Integrator, TanhSinhIntegrator},
+    method::Method,
+    problem::Problem,
+    residual::residual_norms,
+};
 use std::fs::File;
 use std::io::Write;
+use std::time::Duration;
 
 fn save_csv(func: &dyn Function, left: f64, right: f64, node_count: usize, file_path: &str) {
     let mut file = File::create(file_path).unwrap();
@@ -8,10 +15,22 @@ fn save_csv(func: &dyn Function, left: f64, right: f64, node_count: usize, file_
 
     for i in 0..node_count {
         let x = (i as f64) * step + left;
-        write!(file, "{x},{}\n", func.get(x)).unwrap();
+        writeln!(file, "{x},{}", func.get(x)).unwrap();
     }
 }
 
+const RESIDUAL_POINT_COUNT: usize = 200;
+
+/// The residual integrals on a finite width use composite Gauss–Legendre of this order on
+/// this many subintervals per side of the kink, a rule unrelated to the grids of the
+/// methods so that it cannot reproduce their quadrature error.
+const RESIDUAL_RULE_ORDER: usize = 8;
+const RESIDUAL_SUBINTERVALS: usize = 64;
+
+/// Solutions for an infinite width are saved and checked on `[-15, 15]` only, the plots
+/// show no more.
+const UNBOUNDED_EXTENT: f64 = 15.0;
+
 macro_rules! measure {
     ($func:expr) => {{
         use std::time::Instant;
@@ -21,82 +40,295 @@ macro_rules! measure {
     }};
 }
 
+/// How one method did on one problem, a row of `print_summary`.
+pub struct Summary {
+    pub problem: String,
+    pub method: String,
+    pub calculation: Duration,
+    pub residual_max: Option<f64>,
+    /// Largest relative difference to the exact solution in percent, if there is one.
+    pub max_difference: Option<f64>,
+    pub iterations: Option<usize>,
+    /// Why the method failed, the other fields are then empty.
+    pub error: Option<String>,
+}
+
 pub fn test_method(
     method: &dyn Method,
-    m: &dyn Function,
-    w: &dyn Function,
-    parameter: f64,
-    actual: &dyn Function,
+    problem: &Problem,
     comparison_point_count: usize,
-    width: f64,
     out_name: &str,
     out_dir: &str,
-    name_prefix: &str,
-) {
-    let (answer, calc_duration) = measure!(method.solve(m, w, parameter, width));
+) -> Summary {
+    let (m, w) = (
+        problem.birth_probability.as_ref(),
+        problem.death_probability.as_ref(),
+    );
+    let (parameter, width, name_prefix) = (problem.parameter, problem.width, &problem.name);
+    let actual = problem.solution.as_deref();
 
-    let diff = |x| (answer.get(x) - actual.get(x)).abs() / actual.get(x) * 100.0;
+    // Problems are even, `Problem::parse` rejects the others.
+    let (answer, calc_duration) = measure!(method.solve_even(m, w, parameter, width));
+    let (answer, diagnostics) = match answer {
+        Ok(answer) => answer,
+        Err(err) => {
+            let mut stats_file =
+                File::create(format!("{out_dir}/{name_prefix}_{out_name}_stats.txt")).unwrap();
+            writeln!(
+                stats_file,
+                "{name_prefix}_{out_name}:
+\tFailed after {}ms: {err}",
+                calc_duration.as_millis()
+            )
+            .unwrap();
 
-    let mut diff_vals: Vec<f64> = diff
-        .to_vec(0.0, width, comparison_point_count)
-        .iter()
-        .map(|(_, y)| y.to_owned())
+            println!(
+                "{name_prefix}_{out_name}:
+\tFailed after {}ms: {err}",
+                calc_duration.as_millis()
+            );
+            return Summary {
+                problem: name_prefix.clone(),
+                method: out_name.to_string(),
+                calculation: calc_duration,
+                residual_max: None,
+                max_difference: None,
+                iterations: None,
+                error: Some(err.to_string()),
+            };
+        }
+    };
+
+    // Solutions are compared on the whole of `[-extent, extent]` at the same spacing as
+    // `comparison_point_count` points on `[0, extent]`.
+    let extent = if width.is_finite() {
+        width
+    } else {
+        UNBOUNDED_EXTENT
+    };
+    let (left, plot_point_count) = (-extent, 2 * comparison_point_count - 1);
+
+    let residual_points: Vec<f64> = (0..RESIDUAL_POINT_COUNT)
+        .map(|i| (i as f64) * extent / (RESIDUAL_POINT_COUNT - 1) as f64)
         .collect();
-    diff_vals.sort_by(|x, y| x.partial_cmp(y).unwrap());
-
-    let max_diff = diff_vals.iter().last().unwrap().to_owned();
-    let mean = diff_vals.iter().sum::<f64>() / diff_vals.len() as f64;
-    let median = diff_vals[diff_vals.len() / 2];
-
-    let (_, save_duration) = measure!({
-        save_csv(
-            actual,
-            0.0,
-            width,
-            comparison_point_count,
-            &format!("{out_dir}/{name_prefix}_actual.csv"),
-        );
-        save_csv(
-            answer.as_ref(),
-            0.0,
-            width,
-            comparison_point_count,
-            &format!("{out_dir}/{name_prefix}_{out_name}.csv"),
-        );
-        save_csv(
-            &diff,
-            0.0,
-            width,
-            comparison_point_count,
-            &format!("{out_dir}/{name_prefix}_{out_name}_diff.csv"),
-        )
-    });
+    let residual_integrator: Box<dyn Integrator> = if width.is_finite() {
+        Box::new(GaussLegendreIntegrator::composite(
+            RESIDUAL_RULE_ORDER,
+            RESIDUAL_SUBINTERVALS,
+        ))
+    } else {
+        Box::new(TanhSinhIntegrator::new(1.0 / 64.0))
+    };
+    let (residual, residual_duration) = measure!(residual_norms(
+        answer.as_ref(),
+        m,
+        w,
+        parameter,
+        width,
+        residual_integrator.as_ref(),
+        &residual_points,
+    ));
 
-    let mut stats_file =
-        File::create(&format!("{out_dir}/{name_prefix}_{out_name}_stats.txt")).unwrap();
-    writeln!(
-        stats_file,
+    let mut stats = format!(
         "{name_prefix}_{out_name}:
 \tCalculation took {}ms
-\tSaving took {}ms
-\tMax difference {}%\tMean: {}%\tMedian: {}%",
+\tResidual took {}ms
+\tResidual max: {}\tL2: {}",
         calc_duration.as_millis(),
-        save_duration.as_millis(),
-        max_diff,
-        mean,
-        median
-    )
-    .unwrap();
-
-    println!(
-        "{name_prefix}_{out_name}:
-\tCalculation took {}ms
+        residual_duration.as_millis(),
+        residual.max,
+        residual.l2
+    );
+
+    if let Some(iterations) = diagnostics.iterations {
+        stats += &format!("\n\tIterations: {iterations}");
+    }
+    if let Some(contraction_factor) = diagnostics.contraction_factor {
+        stats += &format!("\tContraction factor: {contraction_factor}");
+    }
+    if let Some(operator_norm) = diagnostics.operator_norm {
+        stats += &format!("\tOperator norm: {operator_norm}");
+    }
+    for warning in &diagnostics.warnings {
+        stats += &format!("\n\tWarning: {warning}");
+    }
+
+    let (_, save_duration) = measure!(save_csv(
+        answer.as_ref(),
+        left,
+        extent,
+        plot_point_count,
+        &format!("{out_dir}/{name_prefix}_{out_name}.csv"),
+    ));
+
+    let max_difference = if let Some(actual) = actual {
+        let diff = |x| (answer.get(x) - actual.get(x)).abs() / actual.get(x) * 100.0;
+
+        let mut diff_vals: Vec<f64> = diff
+            .to_vec(left, extent, plot_point_count)
+            .iter()
+            .map(|(_, y)| y.to_owned())
+            .collect();
+        diff_vals.sort_by(f64::total_cmp);
+
+        let max_diff = diff_vals.iter().last().unwrap().to_owned();
+        let mean = diff_vals.iter().sum::<f64>() / diff_vals.len() as f64;
+        let median = diff_vals[diff_vals.len() / 2];
+
+        let (_, diff_save_duration) = measure!({
+            save_csv(
+                actual,
+                left,
+                extent,
+                plot_point_count,
+                &format!("{out_dir}/{name_prefix}_actual.csv"),
+            );
+            save_csv(
+                &diff,
+                left,
+                extent,
+                plot_point_count,
+                &format!("{out_dir}/{name_prefix}_{out_name}_diff.csv"),
+            )
+        });
+
+        stats += &format!(
+            "
 \tSaving took {}ms
 \tMax difference {}%\tMean: {}%\tMedian: {}%",
-        calc_duration.as_millis(),
-        save_duration.as_millis(),
-        max_diff,
-        mean,
-        median
-    );
+            (save_duration + diff_save_duration).as_millis(),
+            max_diff,
+            mean,
+            median
+        );
+        Some(max_diff)
+    } else {
+        stats += &format!("\n\tSaving took {}ms", save_duration.as_millis());
+        None
+    };
+
+    let mut stats_file =
+        File::create(format!("{out_dir}/{name_prefix}_{out_name}_stats.txt")).unwrap();
+    writeln!(stats_file, "{stats}").unwrap();
+
+    println!("{stats}");
+
+    Summary {
+        problem: name_prefix.clone(),
+        method: out_name.to_string(),
+        calculation: calc_duration,
+        residual_max: Some(residual.max),
+        max_difference,
+        iterations: diagnostics.iterations,
+        error: None,
+    }
+}
+
+/// Runs every method on every problem, writing the results of each pair to `out_dir`.
+pub fn run_problems(
+    methods: &[(&str, &dyn Method)],
+    problems: &[Problem],
+    comparison_point_count: usize,
+    out_dir: &str,
+) -> Vec<Summary> {
+    problems
+        .iter()
+        .flat_map(|problem| {
+            methods.iter().map(move |&(name, method)| {
+                test_method(method, problem, comparison_point_count, name, out_dir)
+            })
+        })
+        .collect()
+}
+
+/// Prints one aligned row per summary.
+pub fn print_summary(summaries: &[Summary]) {
+    let header = [
+        "problem",
+        "method",
+        "time ms",
+        "residual max",
+        "max diff %",
+        "iterations",
+    ];
+    let rows: Vec<[String; 6]> = summaries
+        .iter()
+        .map(|summary| {
+            let optional =
+                |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{v:.3e}"));
+            [
+                summary.problem.clone(),
+                summary.method.clone(),
+                summary.calculation.as_millis().to_string(),
+                match &summary.error {
+                    Some(error) => format!("failed: {error}"),
+                    None => optional(summary.residual_max),
+                },
+                optional(summary.max_difference),
+                summary
+                    .iterations
+                    .map_or("-".to_string(), |iterations| iterations.to_string()),
+            ]
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .chain([header[i].len()])
+                .max()
+                .unwrap()
+        })
+        .collect();
+    let line = |cells: Vec<&str>| {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:<width$}"))
+            .collect();
+        println!("{}", cells.join("  ").trim_end());
+    };
+
+    line(header.to_vec());
+    for row in &rows {
+        line(row.iter().map(String::as_str).collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        basis::CosineBasis, galerkin::Galerkin, integrals::TrapezoidIntegrator,
+        linear_equation::LUSolver, method::SolveError, nystrom::NystromMethod, problem::catalog,
+    };
+
+    #[test]
+    fn infinite_width_is_checked_on_a_window() {
+        let out_dir = std::env::temp_dir().join("nikitin-bio-bmk3-infinite-width");
+        std::fs::create_dir_all(&out_dir).unwrap();
+        let out_dir = out_dir.to_str().unwrap();
+
+        let mut problem = catalog().remove(0);
+        problem.width = f64::INFINITY;
+
+        let nystrom = NystromMethod::new(
+            Box::new(LUSolver),
+            Box::new(GaussLegendreIntegrator::composite(8, 20)),
+        );
+        let summary = test_method(&nystrom, &problem, 101, "nystrom", out_dir);
+        assert!(summary.error.is_none());
+        assert!(summary.residual_max.unwrap().is_finite());
+        assert!(summary.max_difference.unwrap().is_finite());
+
+        let galerkin = Galerkin::new(
+            Box::new(TrapezoidIntegrator::new(101)),
+            Box::new(LUSolver),
+            Box::new(|_: f64| 1.0),
+            CosineBasis::new(10),
+        );
+        let summary = test_method(&galerkin, &problem, 101, "galerkin", out_dir);
+        assert_eq!(summary.error, Some(SolveError::UnboundedDomain.to_string()));
+    }
 }
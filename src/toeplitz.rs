@@ -0,0 +1,83 @@
+use crate::fft::{fft, Complex};
+
+/// `n × n` Toeplitz matrix `T[j][i] = t(j - i)` that is applied through a circulant embedding.
+pub struct Toeplitz {
+    n: usize,
+    spectrum: Vec<Complex>,
+}
+
+impl Toeplitz {
+    pub fn new(n: usize, t: impl Fn(isize) -> f64) -> Self {
+        let size = (2 * n).next_power_of_two();
+        let mut spectrum: Vec<Complex> = (0..size).map(|_| Complex::default()).collect();
+        for (d, s) in spectrum.iter_mut().take(n).enumerate() {
+            *s = Complex::new(t(d as isize), 0.0);
+        }
+        for d in 1..n {
+            spectrum[size - d] = Complex::new(t(-(d as isize)), 0.0);
+        }
+        fft(&mut spectrum, false);
+
+        Self { n, spectrum }
+    }
+
+    pub fn apply(&self, v: &[f64]) -> Vec<f64> {
+        let mut data: Vec<Complex> = (0..self.spectrum.len())
+            .map(|i| Complex::new(if i < self.n { v[i] } else { 0.0 }, 0.0))
+            .collect();
+        fft(&mut data, false);
+        for (d, s) in data.iter_mut().zip(&self.spectrum) {
+            *d = *d * *s;
+        }
+        fft(&mut data, true);
+
+        data[..self.n].iter().map(|c| c.re).collect()
+    }
+}
+
+/// `n × n` matrix `A[j][i] = t(j - i) + h(j + i)`.
+pub struct ToeplitzPlusHankel {
+    toeplitz: Toeplitz,
+    // A Hankel matrix is a Toeplitz matrix applied to the reversed vector.
+    hankel: Toeplitz,
+}
+
+impl ToeplitzPlusHankel {
+    pub fn new(n: usize, t: impl Fn(isize) -> f64, h: impl Fn(usize) -> f64) -> Self {
+        Self {
+            toeplitz: Toeplitz::new(n, t),
+            hankel: Toeplitz::new(n, |d| h((n as isize - 1 + d) as usize)),
+        }
+    }
+
+    pub fn apply(&self, v: &[f64]) -> Vec<f64> {
+        let reversed: Vec<f64> = v.iter().rev().copied().collect();
+        self.toeplitz
+            .apply(v)
+            .iter()
+            .zip(self.hankel.apply(&reversed))
+            .map(|(t, h)| t + h)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_like_the_dense_matrix() {
+        let n = 7;
+        let t = |d: isize| 1.0 / (1.0 + (d as f64 - 0.5).powi(2));
+        let h = |s: usize| (s as f64 * 0.3).cos();
+        let v: Vec<f64> = (0..n).map(|i| (i as f64).sqrt() - 1.0).collect();
+
+        let applied = ToeplitzPlusHankel::new(n, t, h).apply(&v);
+        for (j, value) in applied.iter().enumerate() {
+            let dense: f64 = (0..n)
+                .map(|i| (t(j as isize - i as isize) + h(j + i)) * v[i])
+                .sum();
+            assert!((value - dense).abs() < 1e-12, "{value} != {dense}");
+        }
+    }
+}
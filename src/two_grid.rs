@@ -0,0 +1,289 @@
+use rayon::prelude::*;
+
+use crate::{
+    convolution::ConvolutionOperator,
+    functions::Function,
+    integrals::{QuadratureRule, TrapezoidIntegrator},
+    method::{check_finite, Diagnostics, Method, SolveError},
+    nystrom::{apply_kernel, grid, NystromInterpolant, NystromMethod},
+};
+
+/// Atkinson–Brakhage two-grid iteration for the Nyström system of a fine rule. Only the
+/// coarse system is factorized, the fine one is iterated on with
+/// `u <- u + r + (I - K_coarse)^(-1) K_fine r`, `r` being the fine residual. An iteration
+/// applies the fine kernel twice, to `u` and to `r`, plus one `O(n_fine n_coarse)` pass
+/// that extends the coarse correction to the fine nodes, and converges about as fast as
+/// the coarse rule is accurate.
+pub struct TwoGridMethod {
+    coarse: NystromMethod,
+    fine: FineRule,
+    tolerance: f64,
+    max_iterations: usize,
+}
+
+enum FineRule {
+    /// Any rule, the fine kernel is summed directly in `O(n²)`.
+    Rule(Box<dyn QuadratureRule>),
+    /// The trapezoid rule on this many equally spaced nodes, the fine kernel is applied by
+    /// a `ConvolutionOperator` in `O(n log n)`.
+    Uniform(usize),
+}
+
+impl TwoGridMethod {
+    pub fn new(
+        coarse: NystromMethod,
+        fine_rule: Box<dyn QuadratureRule>,
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> Self {
+        Self {
+            coarse,
+            fine: FineRule::Rule(fine_rule),
+            tolerance,
+            max_iterations,
+        }
+    }
+
+    /// Uses the trapezoid rule on `fine_node_count` equally spaced nodes as the fine rule.
+    pub fn convolution(
+        coarse: NystromMethod,
+        fine_node_count: usize,
+        tolerance: f64,
+        max_iterations: usize,
+    ) -> Self {
+        Self {
+            coarse,
+            fine: FineRule::Uniform(fine_node_count),
+            tolerance,
+            max_iterations,
+        }
+    }
+}
+
+/// Linear interpolation of `values` at the ascending `nodes`, constant beyond the ends.
+fn interpolate(nodes: &[f64], values: &[f64], x: f64) -> f64 {
+    let i = nodes.partition_point(|&node| node < x);
+    if i == 0 {
+        values[0]
+    } else if i == nodes.len() {
+        values[nodes.len() - 1]
+    } else {
+        let t = (x - nodes[i - 1]) / (nodes[i] - nodes[i - 1]);
+        values[i - 1] + t * (values[i] - values[i - 1])
+    }
+}
+
+impl Method for TwoGridMethod {
+    fn solve<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<Box<dyn Function + 'a>, SolveError> {
+        self.solve_with_diagnostics(birth_probability, death_probability, parameter, width)
+            .map(|(solution, _)| solution)
+    }
+
+    fn solve_with_diagnostics<'a>(
+        &'a self,
+        birth_probability: &'a dyn Function,
+        death_probability: &'a dyn Function,
+        parameter: f64,
+        width: f64,
+    ) -> Result<(Box<dyn Function + 'a>, Diagnostics), SolveError> {
+        if !width.is_finite() {
+            return Err(SolveError::UnboundedDomain);
+        }
+        let coarse = self
+            .coarse
+            .prepare(birth_probability, death_probability, width)?;
+        let (nodes, weights) = match &self.fine {
+            FineRule::Rule(rule) => grid(rule.as_ref(), width),
+            FineRule::Uniform(node_count) => grid(&TrapezoidIntegrator::new(*node_count), width),
+        };
+        let scales: Vec<f64> = nodes
+            .par_iter()
+            .map(|&x| 1.0 / (1.0 + death_probability.get(x)))
+            .collect();
+        check_finite(&scales, 1)?;
+        let convolution = match self.fine {
+            FineRule::Uniform(node_count) => Some(ConvolutionOperator::new(
+                birth_probability,
+                death_probability,
+                node_count,
+                width,
+            )?),
+            FineRule::Rule(_) => None,
+        };
+
+        let f = move |x: f64| {
+            (birth_probability.get(x) * parameter - death_probability.get(x))
+                / (1.0 + death_probability.get(x))
+        };
+        // `K_fine v` at the fine nodes.
+        let fine_kernel = |v: &[f64]| -> Vec<f64> {
+            match &convolution {
+                Some(convolution) => convolution.apply(v),
+                None => {
+                    let weighted: Vec<f64> = v.iter().zip(&weights).map(|(v, w)| v * w).collect();
+                    apply_kernel(birth_probability, &nodes, &weighted, &nodes)
+                        .iter()
+                        .zip(&scales)
+                        .map(|(kv, scale)| kv * scale)
+                        .collect()
+                }
+            }
+        };
+        // `(I - K_coarse)^(-1) g` at the fine nodes for `g` given there: `g` is interpolated
+        // to the coarse nodes for the solve and the result extended back by the coarse
+        // Nyström interpolant, whose `g` term is the given fine values.
+        let coarse_correction = |g: &[f64]| -> Result<Vec<f64>, SolveError> {
+            let g_coarse: Vec<f64> = coarse
+                .nodes()
+                .iter()
+                .map(|&x| interpolate(&nodes, g, x))
+                .collect();
+            let e = coarse.solve_node_values(&g_coarse)?;
+            let weighted: Vec<f64> = e.iter().zip(coarse.weights()).map(|(e, w)| e * w).collect();
+            Ok(
+                apply_kernel(birth_probability, coarse.nodes(), &weighted, &nodes)
+                    .iter()
+                    .zip(&scales)
+                    .zip(g)
+                    .map(|((ke, scale), g)| g + ke * scale)
+                    .collect(),
+            )
+        };
+
+        let f_nodes: Vec<f64> = nodes.par_iter().map(|&x| f(x)).collect();
+        let initial = coarse.solve_right_hand_side(&f)?;
+        let mut u: Vec<f64> = nodes.par_iter().map(|&x| initial.get(x)).collect();
+        let mut residuals = Vec::new();
+
+        loop {
+            let r: Vec<f64> = fine_kernel(&u)
+                .iter()
+                .zip(&f_nodes)
+                .zip(&u)
+                .map(|((ku, f), u)| f + ku - u)
+                .collect();
+            check_finite(&r, 1)?;
+            let residual = r.iter().fold(0.0, |max, r| f64::max(max, r.abs()));
+            residuals.push(residual);
+
+            if residual <= self.tolerance {
+                break;
+            }
+            if residuals.len() > self.max_iterations {
+                break;
+            }
+
+            let correction = coarse_correction(&fine_kernel(&r))?;
+            for ((u, r), correction) in u.iter_mut().zip(&r).zip(&correction) {
+                *u += r + correction;
+            }
+        }
+
+        let iterations = residuals.len() - 1;
+        let diagnostics = Diagnostics {
+            iterations: Some(iterations),
+            contraction_factor: (iterations > 0 && residuals[0] > 0.0)
+                .then(|| (residuals[iterations] / residuals[0]).powf(1.0 / iterations as f64)),
+            warnings: Some(residuals[iterations])
+                .filter(|&residual| residual > self.tolerance)
+                .map(|residual| {
+                    format!(
+                        "tolerance {} not met after {iterations} iterations, residual {residual}",
+                        self.tolerance
+                    )
+                })
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+
+        Ok((
+            Box::new(NystromInterpolant::new(
+                birth_probability,
+                death_probability,
+                nodes,
+                &weights,
+                &u,
+                f,
+                1.0,
+            )),
+            diagnostics,
+        ))
+    }
+
+    fn even_solution(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{integrals::GaussLegendreIntegrator, linear_equation::LUSolver, problem::catalog};
+
+    fn coarse() -> NystromMethod {
+        NystromMethod::new(Box::new(LUSolver), Box::new(TrapezoidIntegrator::new(161)))
+    }
+
+    fn assert_agree(two_grid: &dyn Method, fine: &dyn Method) {
+        for problem in catalog() {
+            let solve = |method: &dyn Method| {
+                let solution = method
+                    .solve(
+                        problem.birth_probability.as_ref(),
+                        problem.death_probability.as_ref(),
+                        problem.parameter,
+                        problem.width,
+                    )
+                    .unwrap();
+                (0..=30)
+                    .map(|i| solution.get(problem.width * i as f64 / 30.0))
+                    .collect::<Vec<f64>>()
+            };
+            for (a, b) in solve(two_grid).iter().zip(solve(fine)) {
+                assert!((a - b).abs() < 1e-10, "{}: {a} != {b}", problem.name);
+            }
+        }
+    }
+
+    #[test]
+    fn converges_to_the_fine_nystrom_solution() {
+        assert_agree(
+            &TwoGridMethod::convolution(coarse(), 401, 1e-12, 50),
+            &NystromMethod::new(Box::new(LUSolver), Box::new(TrapezoidIntegrator::new(401))),
+        );
+
+        let fine_rule = || Box::new(GaussLegendreIntegrator::composite(4, 60));
+        assert_agree(
+            &TwoGridMethod::new(coarse(), fine_rule(), 1e-12, 50),
+            &NystromMethod::new(Box::new(LUSolver), fine_rule()),
+        );
+    }
+
+    #[test]
+    fn unmet_tolerance_returns_the_last_iterate() {
+        let problem = catalog().remove(0);
+        let method = TwoGridMethod::convolution(coarse(), 401, 1e-15, 1);
+        let (solution, diagnostics) = method
+            .solve_with_diagnostics(
+                problem.birth_probability.as_ref(),
+                problem.death_probability.as_ref(),
+                problem.parameter,
+                problem.width,
+            )
+            .unwrap();
+
+        assert_eq!(diagnostics.iterations, Some(1));
+        assert!(diagnostics
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("not met")));
+        assert!(solution.get(1.0).is_finite());
+    }
+}